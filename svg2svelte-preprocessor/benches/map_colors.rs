@@ -0,0 +1,155 @@
+use regex::Regex;
+use std::time::{Duration, Instant};
+use svg2svelte_preprocessor::{
+    config::Config,
+    svg::{
+        color_mapping::ColorMapping,
+        css_color::CssColor,
+        document::Document,
+        modifier::{Modifier, Pass},
+        passes::map_colors::MapColors,
+        var_color::VarColor,
+    },
+};
+
+const MAPPED_COLORS: usize = 256;
+const FRAMES: usize = 3000;
+const PATHS_PER_FRAME: usize = 20;
+const RUNS: usize = 5;
+
+/**
+ * An export the size and shape of a large Figma one: nested frames of paths
+ * with hex fills and strokes, some of them in upper case.
+ */
+fn large_export(colors: &[String]) -> String {
+    let mut svg = String::from(r#"<svg width="1440" height="1024" fill="none">"#);
+
+    for frame in 0..FRAMES {
+        svg.push_str(format!(r#"<g id="frame-{}">"#, frame).as_str());
+
+        for path in 0..PATHS_PER_FRAME {
+            svg.push_str(
+                format!(
+                    r#"<path d="M{0} {1}L{2} {1}L{2} {3}Z" fill="{4}" stroke="{5}" stroke-width="2"/>"#,
+                    path * 7,
+                    frame,
+                    path * 7 + 5,
+                    frame + 5,
+                    colors[(frame + path) % colors.len()],
+                    colors[(frame * 3 + path) % colors.len()].to_uppercase(),
+                )
+                .as_str(),
+            );
+        }
+
+        svg.push_str("</g>");
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/**
+ * The shortest of several runs of a function, with its last result.
+ */
+fn time<T>(mut f: impl FnMut() -> T) -> (Duration, T) {
+    let mut fastest = Duration::MAX;
+    let mut result = None;
+
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let value = f();
+        fastest = fastest.min(start.elapsed());
+        result = Some(value);
+    }
+
+    (fastest, result.unwrap())
+}
+
+/**
+ * Times mapping the colors of a generated multi-megabyte export with the color index,
+ * against folding one regular expression per mapping over every paint value, as colors
+ * used to be mapped. Run with `cargo bench --bench map_colors`.
+ */
+fn main() {
+    let colors = (0..MAPPED_COLORS)
+        .map(|index| format!("#{:06x}", index * 0x010203))
+        .collect::<Vec<_>>();
+
+    let svg = large_export(&colors);
+    let mut config = Config::default();
+
+    for (index, color) in colors.iter().enumerate() {
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new(color).unwrap(),
+            VarColor::new(format!("--color-{}", index).as_str()).unwrap(),
+        ));
+    }
+
+    let document = Document::parse(svg.as_str()).unwrap();
+    let mut values = Vec::new();
+
+    document.root().walk(&mut |element, _| {
+        for name in ["fill", "stroke"] {
+            values.extend(element.attribute(name).map(String::from));
+        }
+    });
+
+    let (pass_time, output) = time(|| {
+        let mut modifier = Modifier::new(&config, document.clone());
+        MapColors {}.run(&mut modifier).unwrap();
+        modifier.accumulate().to_string()
+    });
+
+    assert!(!output.contains(colors[1].as_str()));
+
+    let index = config.color_mappings().index();
+    let (index_time, indexed) = time(|| {
+        values
+            .iter()
+            .map(|value| {
+                index
+                    .replace(value, |_| true)
+                    .unwrap_or_else(|| value.clone())
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let regexes = colors
+        .iter()
+        .enumerate()
+        .map(|(index, color)| {
+            (
+                Regex::new(format!("(?i){}", color).as_str()).unwrap(),
+                format!("var(--color-{})", index),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let (fold_time, folded) = time(|| {
+        values
+            .iter()
+            .map(|value| {
+                regexes.iter().fold(value.clone(), |value, (regex, var)| {
+                    regex.replace_all(value.as_str(), var.as_str()).to_string()
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+
+    assert_eq!(indexed, folded);
+
+    println!(
+        "{:.1} MB, {} paint values, {} mappings",
+        svg.len() as f64 / 1_000_000.0,
+        values.len(),
+        MAPPED_COLORS
+    );
+    println!("map-colors pass:    {:?}", pass_time);
+    println!("color index:        {:?}", index_time);
+    println!("regex per mapping:  {:?}", fold_time);
+    println!(
+        "speedup:            {:.1}x",
+        fold_time.as_secs_f64() / index_time.as_secs_f64()
+    );
+}
@@ -0,0 +1,286 @@
+use super::palette::{Paint, Palette};
+use crate::{
+    config::Config,
+    svg::{
+        document::{Document, Element},
+        geometry::{self, BoundingBox, Transform},
+        rgb::Rgb,
+    },
+};
+use std::fmt::Display;
+
+/**
+ * Minimum WCAG 2.x AA contrast ratios for normal and large text.
+ */
+const NORMAL_TEXT_MIN_RATIO: f64 = 4.5;
+const LARGE_TEXT_MIN_RATIO: f64 = 3.0;
+
+/**
+ * Elements whose content is never painted directly.
+ */
+const NON_RENDERED_ELEMENTS: &[&str] = &[
+    "defs",
+    "clipPath",
+    "mask",
+    "marker",
+    "pattern",
+    "symbol",
+    "linearGradient",
+    "radialGradient",
+];
+
+const MAX_LABEL_LENGTH: usize = 32;
+
+pub struct ContrastFinding {
+    label: String,
+    foreground: Rgb,
+    background: Rgb,
+    ratio: f64,
+    required_ratio: f64,
+}
+
+pub struct ContrastReport {
+    palette_name: String,
+    findings: Vec<ContrastFinding>,
+    skipped: Vec<String>,
+}
+
+impl ContrastFinding {
+    pub fn passes(&self) -> bool {
+        self.ratio >= self.required_ratio
+    }
+}
+
+impl ContrastReport {
+    pub fn failures(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| !finding.passes())
+            .count()
+    }
+}
+
+impl Display for ContrastReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Theme \"{}\": {} of {} text elements fail",
+            self.palette_name,
+            self.failures(),
+            self.findings.len()
+        )?;
+
+        for finding in self.findings.iter() {
+            writeln!(
+                f,
+                "\t{} {:>5.2}:1 (needs {}:1) {} on {} {}",
+                if finding.passes() { "PASS" } else { "FAIL" },
+                finding.ratio,
+                finding.required_ratio,
+                finding.foreground,
+                finding.background,
+                finding.label
+            )?;
+        }
+
+        for skipped in self.skipped.iter() {
+            writeln!(f, "\tSKIP {}", skipped)?;
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Checks every `<text>` element against the shape it is drawn on, once per palette.
+ * The backdrop is the last painted basic shape before the text that contains its anchor point,
+ * or the palette background when there is none.
+ */
+pub fn audit(config: &Config, document: &Document) -> Vec<ContrastReport> {
+    let root = document.root();
+
+    Palette::all(config)
+        .iter()
+        .map(|palette| {
+            let mut backdrops: Vec<(BoundingBox, Rgb)> = Vec::new();
+            let mut report = ContrastReport {
+                palette_name: palette.name().to_string(),
+                findings: Vec::new(),
+                skipped: Vec::new(),
+            };
+
+            root.walk(&mut |element, ancestors| {
+                let is_rendered = ancestors.iter().all(|ancestor| {
+                    ancestor.name() != "text" && !NON_RENDERED_ELEMENTS.contains(&ancestor.name())
+                });
+
+                if !is_rendered {
+                    return;
+                }
+
+                let fill = inherited_property(element, ancestors, "fill").unwrap_or("black");
+
+                if element.name() != "text" {
+                    let bounds = Transform::of_ancestors(ancestors)
+                        .and_then(|transform| BoundingBox::of_shape(element, &transform));
+
+                    if let (Some(bounds), Paint::Color(rgb)) =
+                        (bounds, palette.resolve(fill, "fill", element, ancestors))
+                    {
+                        backdrops.push((bounds, rgb));
+                    }
+
+                    return;
+                }
+
+                let label = label(element);
+                let foreground = match palette.resolve(fill, "fill", element, ancestors) {
+                    Paint::Color(rgb) => rgb,
+                    Paint::None => return,
+                    Paint::Unresolved(paint) => {
+                        report
+                            .skipped
+                            .push(format!("{} (unresolved fill {})", label, paint));
+                        return;
+                    }
+                };
+
+                let Some(anchor) = BoundingBox::in_root(element, ancestors) else {
+                    report
+                        .skipped
+                        .push(format!("{} (invalid transform)", label));
+                    return;
+                };
+                let (x, y) = (anchor.min_x(), anchor.min_y());
+                let background = backdrops
+                    .iter()
+                    .rev()
+                    .find(|(bounds, _)| bounds.contains(x, y))
+                    .map_or(palette.background(), |(_, rgb)| *rgb);
+
+                let required_ratio = if is_large_text(element, ancestors) {
+                    LARGE_TEXT_MIN_RATIO
+                } else {
+                    NORMAL_TEXT_MIN_RATIO
+                };
+
+                report.findings.push(ContrastFinding {
+                    label,
+                    foreground,
+                    background,
+                    ratio: foreground.contrast_ratio(&background),
+                    required_ratio,
+                });
+            });
+
+            report
+        })
+        .collect()
+}
+
+fn inherited_property<'a>(
+    element: &'a Element,
+    ancestors: &[&'a Element],
+    name: &str,
+) -> Option<&'a str> {
+    std::iter::once(element)
+        .chain(ancestors.iter().rev().copied())
+        .find_map(|element| element.property(name))
+}
+
+/**
+ * Large text is at least 24px, or at least 18.66px (14pt) when bold.
+ */
+fn is_large_text(element: &Element, ancestors: &[&Element]) -> bool {
+    let font_size = inherited_property(element, ancestors, "font-size")
+        .and_then(|size| match size.strip_suffix("pt") {
+            Some(points) => points.parse::<f64>().ok().map(|points| points * 4.0 / 3.0),
+            None => geometry::length(Some(size)),
+        })
+        .unwrap_or(16.0);
+
+    let is_bold = inherited_property(element, ancestors, "font-weight").is_some_and(|weight| {
+        matches!(weight, "bold" | "bolder") || weight.parse::<u32>().is_ok_and(|w| w >= 700)
+    });
+
+    font_size >= 24.0 || (is_bold && font_size >= 18.66)
+}
+
+fn label(element: &Element) -> String {
+    let text = element.text_content();
+    let text = match text.char_indices().nth(MAX_LABEL_LENGTH) {
+        Some((index, _)) => format!("{}...", &text[..index]),
+        None => text,
+    };
+
+    match element.attribute("id") {
+        Some(id) => format!("#{} \"{}\"", id, text),
+        None => format!("\"{}\"", text),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::audit;
+    use crate::{
+        config::Config,
+        svg::{
+            color_mapping::ColorMapping, css_color::CssColor, document::Document,
+            var_color::VarColor,
+        },
+    };
+
+    const INPUT: &str = r##"<svg width="100" height="100">
+        <rect width="100" height="50" fill="#FFFFFF"/>
+        <text id="top" x="10" y="20" fill="#000000">Top</text>
+        <text id="bottom" x="10" y="80" fill="#000000" font-size="30">Bottom</text>
+    </svg>"##;
+
+    #[test]
+    fn test_audit_finds_backdrop_per_theme() {
+        let mut config = Config::default();
+
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("#000000").unwrap(),
+            VarColor::new("--text").unwrap(),
+        ));
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("#ffffff").unwrap(),
+            VarColor::new("--panel").unwrap(),
+        ));
+        config.set_theme_color(
+            "dark",
+            VarColor::new("--text").unwrap(),
+            CssColor::new("#333333").unwrap(),
+        );
+        config.set_theme_color(
+            "dark",
+            VarColor::new("--panel").unwrap(),
+            CssColor::new("#222222").unwrap(),
+        );
+
+        let document = Document::parse(INPUT).unwrap();
+        let reports = audit(&config, &document);
+        let (source, dark) = (&reports[0], &reports[1]);
+
+        assert_eq!(source.failures(), 0);
+        assert_eq!(dark.failures(), 1);
+        assert!(dark.to_string().contains("FAIL"));
+        assert!(dark.to_string().contains("#333333 on #222222 #top"));
+        assert!(dark.to_string().contains("#333333 on #ffffff #bottom"));
+        assert!(dark.to_string().contains("needs 3:1"));
+    }
+
+    #[test]
+    fn test_audit_skips_unresolved_and_hidden_text() {
+        let input = r##"<svg><defs><text fill="red">Hidden</text></defs><text fill="url(#g)">Grad</text></svg>"##;
+        let document = Document::parse(input).unwrap();
+        let reports = audit(&Config::default(), &document);
+
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0]
+            .to_string()
+            .contains("SKIP \"Grad\" (unresolved fill url(#g))"));
+        assert!(!reports[0].to_string().contains("Hidden"));
+    }
+}
@@ -0,0 +1,232 @@
+use super::palette::Palette;
+use crate::{
+    config::Config,
+    svg::{rgb::Rgb, var_color::VarColor},
+};
+use std::fmt::Display;
+
+/**
+ * A color vision deficiency, simulated with the full-severity matrices from
+ * Machado, Oliveira and Fernandes (2009) applied in linear RGB.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Deficiency {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl Deficiency {
+    pub const ALL: [Deficiency; 3] = [
+        Deficiency::Protanopia,
+        Deficiency::Deuteranopia,
+        Deficiency::Tritanopia,
+    ];
+
+    fn matrix(&self) -> [[f64; 3]; 3] {
+        match self {
+            Deficiency::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Deficiency::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Deficiency::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+
+    pub fn simulate(&self, color: &Rgb) -> Rgb {
+        let linear = color.to_linear();
+        let matrix = self.matrix();
+
+        Rgb::from_linear(matrix.map(|row| {
+            row.iter()
+                .zip(linear.iter())
+                .map(|(weight, channel)| weight * channel)
+                .sum()
+        }))
+    }
+}
+
+impl Display for Deficiency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Deficiency::Protanopia => "protanopia",
+            Deficiency::Deuteranopia => "deuteranopia",
+            Deficiency::Tritanopia => "tritanopia",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+pub struct CvdConflict {
+    first: (VarColor, Rgb),
+    second: (VarColor, Rgb),
+    deficiency: Deficiency,
+    delta_e: f64,
+}
+
+pub struct CvdReport {
+    palette_name: String,
+    min_delta_e: f64,
+    conflicts: Vec<CvdConflict>,
+    unresolved: Vec<VarColor>,
+}
+
+impl Display for CvdReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Theme \"{}\": {} indistinguishable pairs (delta E < {})",
+            self.palette_name,
+            self.conflicts.len(),
+            self.min_delta_e
+        )?;
+
+        for conflict in self.conflicts.iter() {
+            writeln!(
+                f,
+                "\tFAIL {:<12} {} ({}) ~ {} ({}) delta E {:.1}",
+                conflict.deficiency.to_string(),
+                conflict.first.0.as_str(),
+                conflict.first.1,
+                conflict.second.0.as_str(),
+                conflict.second.1,
+                conflict.delta_e
+            )?;
+        }
+
+        for var_color in self.unresolved.iter() {
+            writeln!(f, "\tSKIP {} (no color value)", var_color.as_str())?;
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Simulates each color vision deficiency on every palette built from the color mappings.
+ * A pair of variables is flagged when it is distinguishable with normal vision
+ * but falls below the configured delta E under a simulation.
+ */
+pub fn audit(config: &Config) -> Vec<CvdReport> {
+    let min_delta_e = config.cvd_min_delta_e();
+
+    let var_colors = config.color_mappings().var_colors();
+
+    Palette::all(config)
+        .iter()
+        .map(|palette| {
+            let mut resolved = Vec::new();
+            let mut unresolved = Vec::new();
+
+            for var_color in var_colors.iter() {
+                match palette
+                    .resolve_var(var_color)
+                    .and_then(|css_color| Rgb::from_css_color(css_color).ok())
+                {
+                    Some(rgb) => resolved.push(((*var_color).clone(), rgb)),
+                    None => unresolved.push((*var_color).clone()),
+                }
+            }
+
+            let mut conflicts = Vec::new();
+
+            for (index, first) in resolved.iter().enumerate() {
+                for second in resolved[index + 1..].iter() {
+                    if first.1.delta_e(&second.1) < min_delta_e {
+                        continue;
+                    }
+
+                    for deficiency in Deficiency::ALL {
+                        let delta_e = deficiency
+                            .simulate(&first.1)
+                            .delta_e(&deficiency.simulate(&second.1));
+
+                        if delta_e < min_delta_e {
+                            conflicts.push(CvdConflict {
+                                first: first.clone(),
+                                second: second.clone(),
+                                deficiency,
+                                delta_e,
+                            });
+                        }
+                    }
+                }
+            }
+
+            CvdReport {
+                palette_name: palette.name().to_string(),
+                min_delta_e,
+                conflicts,
+                unresolved,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{audit, Deficiency};
+    use crate::{
+        config::Config,
+        svg::{color_mapping::ColorMapping, css_color::CssColor, rgb::Rgb, var_color::VarColor},
+    };
+
+    fn rgb(color: &str) -> Rgb {
+        Rgb::from_css_color(&CssColor::new(color).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_simulation_preserves_greys() {
+        for deficiency in Deficiency::ALL {
+            let grey = deficiency.simulate(&rgb("#808080"));
+
+            assert!(grey.delta_e(&rgb("#808080")) < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_red_green_pair_is_flagged() {
+        let mut config = Config::default();
+
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("#d62728").unwrap(),
+            VarColor::new("--valve-closed").unwrap(),
+        ));
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("#2ca02c").unwrap(),
+            VarColor::new("--valve-open").unwrap(),
+        ));
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("#1f77b4").unwrap(),
+            VarColor::new("--pipe").unwrap(),
+        ));
+        config.set_theme_color(
+            "high-contrast",
+            VarColor::new("--valve-open").unwrap(),
+            CssColor::new("#ffffff").unwrap(),
+        );
+
+        let reports = audit(&config);
+        let (source, high_contrast) = (&reports[0], &reports[1]);
+
+        assert!(source
+            .conflicts
+            .iter()
+            .any(|conflict| conflict.deficiency == Deficiency::Deuteranopia));
+        assert!(source
+            .to_string()
+            .contains("--valve-closed (#d62728) ~ --valve-open"));
+        assert!(!high_contrast.to_string().contains("--valve-open"));
+    }
+}
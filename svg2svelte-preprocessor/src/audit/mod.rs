@@ -0,0 +1,4 @@
+pub mod contrast;
+pub mod cvd;
+pub mod palette;
+pub mod unmapped;
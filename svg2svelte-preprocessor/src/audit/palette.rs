@@ -0,0 +1,195 @@
+use crate::{
+    config::Config,
+    svg::{css_color::CssColor, document::Element, rgb::Rgb, var_color::VarColor},
+    theme::Theme,
+};
+
+/**
+ * Name of the palette made up of the original colors from the SVG export.
+ */
+pub const SOURCE_PALETTE_NAME: &str = "source";
+
+/**
+ * The outcome of resolving an SVG paint value to a concrete color.
+ */
+#[derive(Debug, PartialEq)]
+pub enum Paint {
+    None,
+    Color(Rgb),
+    Unresolved(String),
+}
+
+/**
+ * Resolves paints through the color mappings and, optionally, a theme.
+ * Variables without a theme value fall back to the color they were mapped from.
+ */
+pub struct Palette<'a> {
+    name: &'a str,
+    config: &'a Config,
+    theme: Option<&'a Theme>,
+}
+
+impl<'a> Palette<'a> {
+    /**
+     * The source palette followed by one palette per configured theme, sorted by name.
+     */
+    pub fn all(config: &'a Config) -> Vec<Self> {
+        std::iter::once(Self {
+            name: SOURCE_PALETTE_NAME,
+            config,
+            theme: None,
+        })
+        .chain(config.themes().iter().map(|(name, theme)| Self {
+            name: name.as_str(),
+            config,
+            theme: Some(theme),
+        }))
+        .collect()
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    pub fn background(&self) -> Rgb {
+        self.theme
+            .and_then(|theme| theme.background())
+            .and_then(|color| Rgb::from_css_color(color).ok())
+            .unwrap_or_else(|| {
+                Rgb::from_css_color(&CssColor::new("white").expect("White is a valid color."))
+                    .expect("White is a named color.")
+            })
+    }
+
+    pub fn resolve_var(&self, var_color: &VarColor) -> Option<&'a CssColor> {
+        self.theme
+            .and_then(|theme| theme.color(var_color))
+            .or_else(|| self.config.color_mappings().source_color(var_color))
+    }
+
+    /**
+     * Resolves the value of a paint attribute on an element.
+     * Literal colors are looked up in the color mappings for that attribute and element.
+     */
+    pub fn resolve(
+        &self,
+        paint: &str,
+        attribute_name: &str,
+        element: &Element,
+        ancestors: &[&Element],
+    ) -> Paint {
+        let paint = paint.trim();
+
+        if paint == "none" || paint.eq_ignore_ascii_case("transparent") {
+            return Paint::None;
+        }
+
+        if let Some(inner) = paint
+            .strip_prefix("var(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let (name, fallback) = match inner.split_once(',') {
+                Some((name, fallback)) => (name.trim(), Some(fallback)),
+                None => (inner.trim(), None),
+            };
+
+            let resolved = VarColor::new(name)
+                .ok()
+                .and_then(|var_color| self.resolve_var(&var_color));
+
+            return match (resolved, fallback) {
+                (Some(css_color), _) => self.to_paint(css_color),
+                (None, Some(fallback)) => {
+                    self.resolve(fallback, attribute_name, element, ancestors)
+                }
+                (None, None) => Paint::Unresolved(paint.to_string()),
+            };
+        }
+
+        match CssColor::new(paint) {
+            Ok(css_color) => {
+                match self.config.color_mappings().lookup(
+                    &css_color,
+                    attribute_name,
+                    element,
+                    ancestors,
+                ) {
+                    Some(var_color) => match self.resolve_var(var_color) {
+                        Some(themed) => self.to_paint(themed),
+                        None => self.to_paint(&css_color),
+                    },
+                    None => self.to_paint(&css_color),
+                }
+            }
+            Err(_) => Paint::Unresolved(paint.to_string()),
+        }
+    }
+
+    fn to_paint(&self, css_color: &CssColor) -> Paint {
+        match Rgb::from_css_color(css_color) {
+            Ok(rgb) => Paint::Color(rgb),
+            Err(_) if css_color.as_str() == "transparent" => Paint::None,
+            Err(_) => Paint::Unresolved(css_color.as_str().to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Paint, Palette};
+    use crate::{
+        config::Config,
+        svg::{
+            color_mapping::ColorMapping, css_color::CssColor, document::Document, rgb::Rgb,
+            var_color::VarColor,
+        },
+    };
+
+    fn color(value: &str) -> Paint {
+        Paint::Color(Rgb::from_css_color(&CssColor::new(value).unwrap()).unwrap())
+    }
+
+    #[test]
+    fn test_resolve_through_mappings_and_themes() {
+        let mut config = Config::default();
+
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("#ff0000").unwrap(),
+            VarColor::new("--alarm").unwrap(),
+        ));
+        config.set_theme_color(
+            "dark",
+            VarColor::new("--alarm").unwrap(),
+            CssColor::new("#aa0000").unwrap(),
+        );
+
+        let document = Document::parse("<svg><path/></svg>").unwrap();
+        let path = document.root().child_elements().next().unwrap();
+        let palettes = Palette::all(&config);
+        let (source, dark) = (&palettes[0], &palettes[1]);
+
+        assert_eq!(dark.name(), "dark");
+        assert_eq!(
+            source.resolve("#FF0000", "fill", path, &[]),
+            color("#ff0000")
+        );
+        assert_eq!(dark.resolve("#FF0000", "fill", path, &[]), color("#aa0000"));
+        assert_eq!(
+            dark.resolve("var(--alarm)", "fill", path, &[]),
+            color("#aa0000")
+        );
+        assert_eq!(
+            source.resolve("var(--alarm)", "fill", path, &[]),
+            color("#ff0000")
+        );
+        assert_eq!(
+            dark.resolve("var(--missing, blue)", "fill", path, &[]),
+            color("blue")
+        );
+        assert_eq!(dark.resolve("none", "fill", path, &[]), Paint::None);
+        assert!(matches!(
+            dark.resolve("url(#paint0)", "fill", path, &[]),
+            Paint::Unresolved(_)
+        ));
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{
+    css::stylesheet::{visit_declarations, Stylesheet},
+    svg::{color_mapping::PAINT_ATTRIBUTES, css_color::CssColor, document::Document, rgb::Rgb},
+};
+use regex::Regex;
+use std::fmt::Display;
+
+/**
+ * Matches, in order of preference, a `url()` reference, a color function, a hex color
+ * and a word with the parenthesis that may follow it.
+ */
+const COLOR_PATTERN: &str = r"(?i)(url\([^)]*\))|\b((?:rgba?|hsla?|hwb|lab|lch|oklab|oklch|color)\([^)]*\))|(#[0-9a-z]+)|(-*[a-z][a-z0-9-]*)(\()?";
+
+pub struct UnmappedColor {
+    color: String,
+    location: String,
+}
+
+/**
+ * Literal colors left in paint values after the color mappings were applied.
+ */
+pub struct UnmappedReport {
+    source: String,
+    colors: Vec<UnmappedColor>,
+}
+
+impl UnmappedReport {
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+}
+
+impl Display for UnmappedReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} unmapped colors in {}",
+            self.colors.len(),
+            self.source
+        )?;
+
+        for unmapped in self.colors.iter() {
+            writeln!(f, "\t{} {}", unmapped.color, unmapped.location)?;
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Reports literal colors in paint attributes and inline `style` declarations of a document.
+ */
+pub fn audit_document(document: &Document) -> UnmappedReport {
+    let color_regex = Regex::new(COLOR_PATTERN).expect("Failed to parse color pattern.");
+    let mut colors = Vec::new();
+
+    document.root().walk(&mut |element, _| {
+        let name = match element.attribute("id") {
+            Some(id) => format!("<{} id=\"{}\">", element.name(), id),
+            None => format!("<{}>", element.name()),
+        };
+
+        for attribute in element.attributes() {
+            let values = match attribute.name() {
+                "style" => Stylesheet::parse_inline(attribute.value())
+                    .unwrap_or_default()
+                    .iter()
+                    .filter(|declaration| PAINT_ATTRIBUTES.contains(&declaration.property()))
+                    .map(|declaration| {
+                        (
+                            declaration.property().to_string(),
+                            declaration.value().to_string(),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+                name if PAINT_ATTRIBUTES.contains(&name) => {
+                    vec![(name.to_string(), attribute.value().to_string())]
+                }
+                _ => continue,
+            };
+
+            for (property, value) in values {
+                colors.extend(literal_colors(&color_regex, value.as_str()).map(|color| {
+                    UnmappedColor {
+                        color,
+                        location: format!("{} {}", name, property),
+                    }
+                }));
+            }
+        }
+    });
+
+    UnmappedReport {
+        source: "the SVG".to_string(),
+        colors,
+    }
+}
+
+/**
+ * Reports literal colors in paint declarations of a stylesheet, with their lines.
+ */
+pub fn audit_stylesheet(source: &str, stylesheet: &Stylesheet) -> UnmappedReport {
+    let color_regex = Regex::new(COLOR_PATTERN).expect("Failed to parse color pattern.");
+    let mut colors = Vec::new();
+
+    visit_declarations(stylesheet.items(), &mut |declaration| {
+        if !PAINT_ATTRIBUTES.contains(&declaration.property()) {
+            return;
+        }
+
+        colors.extend(
+            literal_colors(&color_regex, declaration.value()).map(|color| UnmappedColor {
+                color,
+                location: format!("line {} {}", declaration.line(), declaration.property()),
+            }),
+        );
+    });
+
+    UnmappedReport {
+        source: source.to_string(),
+        colors,
+    }
+}
+
+/**
+ * The literal colors in a paint value. Variables, `url()` references and keywords such as
+ * `none` or `currentColor` are not colors.
+ */
+fn literal_colors<'a>(color_regex: &'a Regex, value: &'a str) -> impl Iterator<Item = String> + 'a {
+    color_regex.captures_iter(value).filter_map(|captures| {
+        if let Some(function) = captures.get(2) {
+            return Some(function.as_str().to_string());
+        }
+
+        let word = captures.get(3).or_else(|| {
+            captures
+                .get(4)
+                .filter(|word| !word.as_str().starts_with('-') && captures.get(5).is_none())
+        })?;
+
+        CssColor::new(word.as_str())
+            .ok()
+            .filter(|color| Rgb::from_css_color(color).is_ok())
+            .map(|_| word.as_str().to_string())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{audit_document, audit_stylesheet, literal_colors, COLOR_PATTERN};
+    use crate::{css::stylesheet::Stylesheet, svg::document::Document};
+    use regex::Regex;
+
+    #[test]
+    fn test_literal_colors() {
+        let color_regex = Regex::new(COLOR_PATTERN).unwrap();
+        let colors = |value: &str| literal_colors(&color_regex, value).collect::<Vec<_>>();
+
+        assert_eq!(colors("#0000FF"), vec!["#0000FF"]);
+        assert_eq!(colors("Red"), vec!["Red"]);
+        assert_eq!(colors("rgb(0, 0, 0) none"), vec!["rgb(0, 0, 0)"]);
+        assert_eq!(colors("var(--red, blue)"), vec!["blue"]);
+        assert!(colors("var(--red)").is_empty());
+        assert!(colors("url(#abc) currentColor").is_empty());
+        assert!(colors("#paint0 transparent none").is_empty());
+    }
+
+    #[test]
+    fn test_audit_reports_locations() {
+        let document = Document::parse(
+            r##"<svg><path id="pipe" fill="var(--pipe)" stroke="#333"/><rect style="fill: red; opacity: 0.5"/></svg>"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            audit_document(&document).to_string(),
+            "2 unmapped colors in the SVG\n\t#333 <path id=\"pipe\"> stroke\n\tred <rect> fill\n"
+        );
+
+        let stylesheet = Stylesheet::parse(
+            ".a {\n\tfill: var(--a);\n}\n\n.b {\n\tstroke: #fff;\n\tcolor: red;\n}",
+        )
+        .unwrap();
+        let report = audit_stylesheet("the style file", &stylesheet);
+
+        assert!(!report.is_empty());
+        assert_eq!(
+            report.to_string(),
+            "2 unmapped colors in the style file\n\t#fff line 6 stroke\n\tred line 7 color\n"
+        );
+    }
+}
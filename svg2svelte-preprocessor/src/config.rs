@@ -1,16 +1,52 @@
-use crate::svg::{css_color::CssColor, var_color::VarColor};
+use crate::{
+    css::{inline_style::InlineStyles, style_format::StyleFormat},
+    svelte::{kind::ComponentKind, target::SvelteTarget},
+    svg::{
+        accessibility::Accessibility,
+        color_mapping::{ColorMapping, ColorMappings},
+        css_color::CssColor,
+        modifier::PassConfig,
+        passes::{
+            extract_classes::ExtractClasses, extract_images::Asset, extract_styles::ExtractStyles,
+        },
+        token::TokenMapping,
+        var_color::VarColor,
+    },
+    theme::Theme,
+};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::BTreeMap, fmt::Display};
 
 const CONFIG_FILE_NAME: &str = "config.json";
+const DEFAULT_CVD_MIN_DELTA_E: f64 = 10.0;
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
     style_file_path: String,
     svg_file_path: String,
     output_file_path: String,
-    color_mappings: HashMap<CssColor, VarColor>,
+    color_mappings: ColorMappings,
+    #[serde(default)]
+    token_mappings: Vec<TokenMapping>,
+    #[serde(default)]
+    themes: BTreeMap<String, Theme>,
+    #[serde(default = "Config::default_cvd_min_delta_e")]
+    cvd_min_delta_e: f64,
+    #[serde(default)]
+    generate_script: bool,
+    #[serde(default)]
+    svelte_target: SvelteTarget,
+    #[serde(default = "Config::default_scope_ids")]
+    scope_ids: bool,
+    #[serde(default)]
+    component_kinds: Vec<ComponentKind>,
+    #[serde(default)]
+    accessibility: Accessibility,
+    #[serde(default)]
+    style_format: StyleFormat,
+    #[serde(default = "PassConfig::defaults")]
+    passes: Vec<PassConfig>,
 }
 
 impl Default for Config {
@@ -19,41 +55,282 @@ impl Default for Config {
             style_file_path: "style.css".to_string(),
             svg_file_path: "Background-Light.svg".to_string(),
             output_file_path: "Diagram.svelte".to_string(),
-            color_mappings: HashMap::new(),
+            color_mappings: ColorMappings::default(),
+            token_mappings: Vec::new(),
+            themes: BTreeMap::new(),
+            cvd_min_delta_e: DEFAULT_CVD_MIN_DELTA_E,
+            generate_script: false,
+            svelte_target: SvelteTarget::default(),
+            scope_ids: true,
+            component_kinds: Vec::new(),
+            accessibility: Accessibility::default(),
+            style_format: StyleFormat::default(),
+            passes: PassConfig::defaults(),
         }
     }
 }
 
 impl Display for Config {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let color_mappings = if self.color_mappings.len() == 0 {
+        let color_mappings = if self.color_mappings.is_empty() {
             "\tNone".to_string()
         } else {
             self.color_mappings
                 .iter()
-                .map(|(css_color, var_color)| {
-                    format!("\t{} -> {}\n", css_color.as_str(), var_color.as_str())
+                .map(|mapping| format!("\t{}\n", mapping))
+                .collect::<String>()
+        };
+
+        let token_mappings = if self.token_mappings.is_empty() {
+            "\tNone".to_string()
+        } else {
+            self.token_mappings
+                .iter()
+                .map(|mapping| {
+                    format!(
+                        "\t{}: {} -> {}\n",
+                        mapping.property(),
+                        mapping.value(),
+                        mapping.var().as_str()
+                    )
                 })
                 .collect::<String>()
         };
 
+        let component_kinds = if self.component_kinds.is_empty() {
+            "\tNone".to_string()
+        } else {
+            self.component_kinds
+                .iter()
+                .map(|kind| format!("\t{}\n", kind))
+                .collect::<String>()
+        };
+
+        let passes = if self.passes.is_empty() {
+            "\tNone".to_string()
+        } else {
+            self.passes
+                .iter()
+                .enumerate()
+                .map(|(index, pass)| format!("\t{}. {}\n", index + 1, pass))
+                .collect::<String>()
+        };
+
+        let themes = if self.themes.is_empty() {
+            "\tNone".to_string()
+        } else {
+            self.themes
+                .iter()
+                .map(|(name, theme)| format!("\t{} ({} colors)\n", name, theme.colors().len()))
+                .collect::<String>()
+        };
+
         write!(
             f,
-            "Current configuration:\nStyle file path: \"{}\"\nSVG file path: \"{}\"\nOutput file path: \"{}\"\nColor mappings:\n{}\n",
-            self.style_file_path, self.svg_file_path, self.output_file_path, color_mappings
+            "Current configuration:\nStyle file path: \"{}\"\nSVG file path: \"{}\"\nOutput file path: \"{}\"\nGenerate script: {}\nSvelte target: {}\nScope ids: {}\nAccessibility: {}\nStyle format: {}\nPasses:\n{}\nColor mappings:\n{}\nToken mappings:\n{}\nComponent kinds:\n{}\nThemes:\n{}\n",
+            self.style_file_path,
+            self.svg_file_path,
+            self.output_file_path,
+            if self.generate_script { "yes" } else { "no" },
+            self.svelte_target,
+            if self.scope_ids { "yes" } else { "no" },
+            self.accessibility,
+            self.style_format,
+            passes,
+            color_mappings,
+            token_mappings,
+            component_kinds,
+            themes
         )
     }
 }
 
 impl Config {
+    fn default_cvd_min_delta_e() -> f64 {
+        DEFAULT_CVD_MIN_DELTA_E
+    }
+
+    fn default_scope_ids() -> bool {
+        true
+    }
+
     #[inline(always)]
-    pub fn color_mappings(&self) -> &HashMap<CssColor, VarColor> {
+    pub fn color_mappings(&self) -> &ColorMappings {
         &self.color_mappings
     }
 
     #[inline(always)]
-    pub fn set_color_mapping(&mut self, css_color: CssColor, var_color: VarColor) {
-        self.color_mappings.insert(css_color, var_color);
+    pub fn set_color_mapping(&mut self, color_mapping: ColorMapping) {
+        self.color_mappings.insert(color_mapping);
+    }
+
+    #[inline(always)]
+    pub fn token_mappings(&self) -> &[TokenMapping] {
+        &self.token_mappings
+    }
+
+    /**
+     * Adds a token mapping, replacing any mapping for the same property and value.
+     */
+    pub fn set_token_mapping(&mut self, token_mapping: TokenMapping) {
+        self.token_mappings.retain(|mapping| {
+            mapping.property() != token_mapping.property()
+                || mapping.value() != token_mapping.value()
+        });
+        self.token_mappings.push(token_mapping);
+    }
+
+    #[inline(always)]
+    pub fn themes(&self) -> &BTreeMap<String, Theme> {
+        &self.themes
+    }
+
+    #[inline(always)]
+    pub fn cvd_min_delta_e(&self) -> f64 {
+        self.cvd_min_delta_e
+    }
+
+    /**
+     * Whether components get a `<script lang="ts">` block exporting `class`, `style` and `theme` props.
+     */
+    #[inline(always)]
+    pub fn generate_script(&self) -> bool {
+        self.generate_script
+    }
+
+    #[inline(always)]
+    pub fn set_generate_script(&mut self, generate_script: bool) {
+        self.generate_script = generate_script;
+    }
+
+    #[inline(always)]
+    pub fn svelte_target(&self) -> SvelteTarget {
+        self.svelte_target
+    }
+
+    #[inline(always)]
+    pub fn set_svelte_target(&mut self, svelte_target: SvelteTarget) {
+        self.svelte_target = svelte_target;
+    }
+
+    /**
+     * Whether ids and references to them get a per-instance prefix in generated components.
+     */
+    #[inline(always)]
+    pub fn scope_ids(&self) -> bool {
+        self.scope_ids
+    }
+
+    #[inline(always)]
+    pub fn set_scope_ids(&mut self, scope_ids: bool) {
+        self.scope_ids = scope_ids;
+    }
+
+    #[inline(always)]
+    pub fn component_kinds(&self) -> &[ComponentKind] {
+        &self.component_kinds
+    }
+
+    /**
+     * Adds a component kind, replacing any kind with the same name.
+     */
+    pub fn set_component_kind(&mut self, component_kind: ComponentKind) {
+        self.component_kinds
+            .retain(|kind| kind.kind() != component_kind.kind());
+        self.component_kinds.push(component_kind);
+    }
+
+    /**
+     * The passes run over the document, in order.
+     */
+    #[inline(always)]
+    pub fn passes(&self) -> &[PassConfig] {
+        &self.passes
+    }
+
+    /**
+     * Sets the inline style handling of every `extract-styles` pass. Without one, the pass
+     * is added ahead of the passes that work on its output.
+     */
+    pub fn set_inline_styles(&mut self, inline_styles: InlineStyles) {
+        let mut found = false;
+
+        for pass in self.passes.iter_mut() {
+            if let PassConfig::ExtractStyles(extract_styles) = pass {
+                *extract_styles = ExtractStyles::new(inline_styles);
+                found = true;
+            }
+        }
+
+        if !found {
+            let index = self
+                .passes
+                .iter()
+                .position(|pass| {
+                    matches!(
+                        pass,
+                        PassConfig::Optimize(_)
+                            | PassConfig::ExtractClasses(_)
+                            | PassConfig::ExtractImages(_)
+                    )
+                })
+                .unwrap_or(self.passes.len());
+
+            self.passes.insert(
+                index,
+                PassConfig::ExtractStyles(ExtractStyles::new(inline_styles)),
+            );
+        }
+    }
+
+    #[inline(always)]
+    pub fn style_format(&self) -> StyleFormat {
+        self.style_format
+    }
+
+    #[inline(always)]
+    pub fn set_style_format(&mut self, style_format: StyleFormat) {
+        self.style_format = style_format;
+    }
+
+    /**
+     * Whether the passes include `extract-classes`.
+     */
+    pub fn extract_classes(&self) -> bool {
+        self.passes
+            .iter()
+            .any(|pass| matches!(pass, PassConfig::ExtractClasses(_)))
+    }
+
+    /**
+     * Adds the `extract-classes` pass at the end of the passes, or removes it.
+     */
+    pub fn set_extract_classes(&mut self, extract_classes: bool) {
+        self.passes
+            .retain(|pass| !matches!(pass, PassConfig::ExtractClasses(_)));
+
+        if extract_classes {
+            self.passes
+                .push(PassConfig::ExtractClasses(ExtractClasses {}));
+        }
+    }
+
+    #[inline(always)]
+    pub fn accessibility(&self) -> &Accessibility {
+        &self.accessibility
+    }
+
+    #[inline(always)]
+    pub fn set_accessibility(&mut self, accessibility: Accessibility) {
+        self.accessibility = accessibility;
+    }
+
+    #[inline(always)]
+    pub fn set_theme_color(&mut self, theme_name: &str, var_color: VarColor, css_color: CssColor) {
+        self.themes
+            .entry(theme_name.to_string())
+            .or_default()
+            .set_color(var_color, css_color);
     }
 
     #[inline(always)]
@@ -61,6 +338,16 @@ impl Config {
         self.style_file_path = style_file_path.to_string();
     }
 
+    /**
+     * The file name of the SVG file, without its directory.
+     */
+    pub fn svg_file_name(&self) -> String {
+        std::path::Path::new(self.svg_file_path.as_str())
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.svg_file_path.clone())
+    }
+
     #[inline(always)]
     pub fn set_svg_file_path(&mut self, svg_file_path: &str) {
         self.svg_file_path = svg_file_path.to_string();
@@ -71,6 +358,37 @@ impl Config {
         self.output_file_path = output_file_path.to_string();
     }
 
+    /**
+     * The component name in kebab case, taken from the output file name (`Diagram.svelte` is `diagram`).
+     */
+    pub fn component_name(&self) -> String {
+        let stem = std::path::Path::new(self.output_file_path.as_str())
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut name = String::new();
+
+        for (index, c) in stem.chars().enumerate() {
+            match c {
+                c if c.is_ascii_uppercase() => {
+                    if index > 0 && !name.ends_with('-') {
+                        name.push('-');
+                    }
+                    name.push(c.to_ascii_lowercase());
+                }
+                c if c.is_ascii_alphanumeric() => name.push(c),
+                _ if !name.is_empty() && !name.ends_with('-') => name.push('-'),
+                _ => {}
+            }
+        }
+
+        match name.trim_end_matches('-') {
+            "" => "component".to_string(),
+            name => name.to_string(),
+        }
+    }
+
     pub fn from_file() -> Result<Self> {
         let config_file = std::fs::read_to_string(CONFIG_FILE_NAME)?;
         let config = serde_json::from_str::<Self>(config_file.as_str())?;
@@ -91,6 +409,42 @@ impl Config {
         Ok(())
     }
 
+    /**
+     * Writes the manifest next to the output file, as `<name>.manifest.json` and `<name>.manifest.d.ts`.
+     */
+    pub fn output_manifest(&self, raw_json_text: &str, raw_declaration_text: &str) -> Result<()> {
+        let output_path = std::path::Path::new(self.output_file_path.as_str());
+
+        std::fs::write(output_path.with_extension("manifest.json"), raw_json_text)?;
+        std::fs::write(
+            output_path.with_extension("manifest.d.ts"),
+            raw_declaration_text,
+        )?;
+
+        Ok(())
+    }
+
+    /**
+     * Writes the assets taken from the SVG, at their paths relative to the output file.
+     */
+    pub fn output_assets(&self, assets: &[Asset]) -> Result<()> {
+        let output_directory = std::path::Path::new(self.output_file_path.as_str())
+            .parent()
+            .unwrap_or(std::path::Path::new(""));
+
+        for asset in assets {
+            let path = output_directory.join(asset.path());
+
+            if let Some(directory) = path.parent() {
+                std::fs::create_dir_all(directory)?;
+            }
+
+            std::fs::write(path, asset.data())?;
+        }
+
+        Ok(())
+    }
+
     pub fn read_style_file(&self) -> Result<String> {
         let style_file = std::fs::read_to_string(self.style_file_path.as_str())?;
 
@@ -103,3 +457,76 @@ impl Config {
         Ok(svg_file)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Config;
+    use crate::css::inline_style::InlineStyles;
+
+    #[test]
+    fn test_component_name() {
+        let mut config = Config::default();
+
+        assert_eq!(config.component_name(), "diagram");
+
+        config.set_output_file_path("out/PropulsionDiagram_v2.svelte");
+        assert_eq!(config.component_name(), "propulsion-diagram-v2");
+
+        config.set_output_file_path("");
+        assert_eq!(config.component_name(), "component");
+    }
+
+    #[test]
+    fn test_passes() {
+        let json = r#"{
+            "style_file_path": "style.css",
+            "svg_file_path": "diagram.svg",
+            "output_file_path": "Diagram.svelte",
+            "color_mappings": [],
+            "passes": [
+                { "pass": "map-colors" },
+                { "pass": "optimize", "precision": 2 }
+            ]
+        }"#;
+
+        let mut config = serde_json::from_str::<Config>(json).unwrap();
+        let passes = |config: &Config| {
+            config
+                .passes()
+                .iter()
+                .map(|pass| pass.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        config.set_inline_styles(InlineStyles::Classes);
+
+        assert_eq!(
+            passes(&config),
+            vec![
+                "map-colors",
+                "extract-styles (inline styles classes)",
+                "optimize (precision 2)",
+            ]
+        );
+
+        config.set_inline_styles(InlineStyles::Keep);
+
+        let saved = serde_json::to_string(&config).unwrap();
+
+        assert_eq!(
+            passes(&serde_json::from_str::<Config>(saved.as_str()).unwrap()),
+            vec![
+                "map-colors",
+                "extract-styles (inline styles keep)",
+                "optimize (precision 2)",
+            ]
+        );
+        assert_eq!(
+            serde_json::from_str::<Config>(r#"{"style_file_path": "", "svg_file_path": "", "output_file_path": "", "color_mappings": []}"#)
+                .unwrap()
+                .passes()
+                .len(),
+            6
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/**
+ * CSS properties that SVG also accepts as presentation attributes.
+ */
+pub const PRESENTATION_ATTRIBUTES: &[&str] = &[
+    "clip-path",
+    "clip-rule",
+    "color",
+    "display",
+    "dominant-baseline",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "filter",
+    "flood-color",
+    "flood-opacity",
+    "font-family",
+    "font-size",
+    "font-style",
+    "font-weight",
+    "letter-spacing",
+    "lighting-color",
+    "marker-end",
+    "marker-mid",
+    "marker-start",
+    "mask",
+    "opacity",
+    "paint-order",
+    "shape-rendering",
+    "stop-color",
+    "stop-opacity",
+    "stroke",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "text-anchor",
+    "text-decoration",
+    "vector-effect",
+    "visibility",
+];
+
+/**
+ * What happens to inline `style` attributes when styles are extracted.
+ *
+ * - `keep` leaves them on the elements.
+ * - `attributes` turns declarations of presentation properties into presentation attributes.
+ *   Other declarations and `!important` ones stay inline.
+ * - `classes` moves each distinct inline style into a generated class in the component style.
+ */
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum InlineStyles {
+    #[default]
+    Keep,
+    Attributes,
+    Classes,
+}
+
+impl InlineStyles {
+    pub fn new(name: &str) -> Result<Self> {
+        match name {
+            "keep" => Ok(InlineStyles::Keep),
+            "attributes" => Ok(InlineStyles::Attributes),
+            "classes" => Ok(InlineStyles::Classes),
+            _ => Err(anyhow!("Unsupported inline style handling.")),
+        }
+    }
+}
+
+impl Display for InlineStyles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            InlineStyles::Keep => "keep",
+            InlineStyles::Attributes => "attributes",
+            InlineStyles::Classes => "classes",
+        };
+
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,5 @@
+pub mod inline_style;
+pub mod style_format;
+pub mod stylesheet;
+pub mod token;
+pub mod validator;
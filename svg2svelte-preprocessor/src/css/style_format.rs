@@ -0,0 +1,54 @@
+use super::stylesheet::Stylesheet;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/**
+ * How the component style is written into the `<style>` block.
+ *
+ * - `keep` writes it as it was authored.
+ * - `minify` drops comments and optional whitespace.
+ * - `pretty` writes one declaration per line, indented with tabs.
+ */
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleFormat {
+    #[default]
+    Keep,
+    Minify,
+    Pretty,
+}
+
+impl StyleFormat {
+    pub fn new(name: &str) -> Result<Self> {
+        match name {
+            "keep" => Ok(StyleFormat::Keep),
+            "minify" => Ok(StyleFormat::Minify),
+            "pretty" => Ok(StyleFormat::Pretty),
+            _ => Err(anyhow!("Unsupported style format.")),
+        }
+    }
+
+    /**
+     * Formats a stylesheet. Anything other than `keep` parses it first.
+     */
+    pub fn apply(self, style: &str) -> Result<String> {
+        match self {
+            StyleFormat::Keep => Ok(style.to_string()),
+            StyleFormat::Minify => Ok(Stylesheet::parse(style)?.minified()),
+            StyleFormat::Pretty => Ok(Stylesheet::parse(style)?.pretty()),
+        }
+    }
+}
+
+impl Display for StyleFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            StyleFormat::Keep => "keep",
+            StyleFormat::Minify => "minify",
+            StyleFormat::Pretty => "pretty",
+        };
+
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,836 @@
+use super::token::{tokenize, Token, TokenKind};
+use anyhow::{anyhow, Result};
+use std::ops::Range;
+
+/**
+ * At-rules whose block holds declarations rather than rules.
+ */
+const DECLARATION_AT_RULES: &[&str] = &[
+    "font-face",
+    "page",
+    "property",
+    "counter-style",
+    "font-palette-values",
+    "viewport",
+];
+
+/**
+ * A parsed stylesheet. The source is kept, so declaration values can be rewritten
+ * in place without reformatting anything else.
+ */
+#[derive(Debug, PartialEq)]
+pub struct Stylesheet {
+    source: String,
+    items: Vec<Item>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Item {
+    Rule(Rule),
+    AtRule(AtRule),
+}
+
+/**
+ * A style rule. The selector is written with comments removed and whitespace collapsed.
+ */
+#[derive(Debug, PartialEq)]
+pub struct Rule {
+    selector: String,
+    selector_range: Range<usize>,
+    line: usize,
+    declarations: Vec<Declaration>,
+}
+
+/**
+ * An at-rule such as `@media`, `@font-face` or `@import`.
+ */
+#[derive(Debug, PartialEq)]
+pub struct AtRule {
+    name: String,
+    prelude: String,
+    line: usize,
+    body: AtRuleBody,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AtRuleBody {
+    None,
+    Items(Vec<Item>),
+    Declarations(Vec<Declaration>),
+}
+
+/**
+ * A declaration. Property names other than custom properties are lowercased,
+ * and the value is trimmed and includes any `!important`.
+ */
+#[derive(Debug, PartialEq)]
+pub struct Declaration {
+    property: String,
+    value: String,
+    value_range: Range<usize>,
+    line: usize,
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Stylesheet {
+    /**
+     * Parses a stylesheet. Errors name the line they were found on.
+     */
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut parser = Parser {
+            source,
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+
+        let items = parser.items(None)?;
+
+        Ok(Self {
+            source: source.to_string(),
+            items,
+        })
+    }
+
+    /**
+     * Parses the declarations of an inline `style` attribute.
+     */
+    pub fn parse_inline(style: &str) -> Result<Vec<Declaration>> {
+        let mut parser = Parser {
+            source: style,
+            tokens: tokenize(style)?,
+            position: 0,
+        };
+
+        parser.declarations(None)
+    }
+
+    /**
+     * Rewrites declaration values, keeping the rest of the source as written.
+     * The callback receives each declaration and returns a replacement value.
+     */
+    pub fn map_values(&self, mut map: impl FnMut(&Declaration) -> Option<String>) -> String {
+        let mut replacements = Vec::new();
+
+        visit_declarations(&self.items, &mut |declaration| {
+            if let Some(value) = map(declaration) {
+                if value != declaration.value {
+                    replacements.push((declaration.value_range.clone(), value));
+                }
+            }
+        });
+
+        let mut result = self.source.clone();
+
+        for (range, value) in replacements.into_iter().rev() {
+            result.replace_range(range, value.as_str());
+        }
+
+        result
+    }
+
+    /**
+     * Rewrites `#id` selectors, keeping the rest of the source as written. The callback
+     * receives each id and returns a replacement for the whole `#id`. Ids inside attribute
+     * selectors and strings are left alone.
+     */
+    pub fn map_selector_ids(&self, mut map: impl FnMut(&str) -> Option<String>) -> String {
+        let mut replacements = Vec::new();
+
+        visit_rules(&self.items, &mut |rule| {
+            let offset = rule.selector_range.start;
+            let selector = &self.source[rule.selector_range.clone()];
+            let tokens = tokenize(selector).expect("Selectors were tokenized when parsed.");
+            let mut depth = 0;
+
+            for token in tokens {
+                match token.kind() {
+                    TokenKind::OpenBracket => depth += 1,
+                    TokenKind::CloseBracket => depth -= 1,
+                    TokenKind::Word if depth == 0 => {
+                        let word = &selector[token.range()];
+
+                        for (index, _) in word.match_indices('#') {
+                            let id = &word[index + 1..];
+                            let length = id
+                                .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+                                .unwrap_or(id.len());
+
+                            if let Some(replacement) = map(&id[..length]) {
+                                let start = offset + token.range().start + index;
+
+                                replacements.push((start..start + 1 + length, replacement));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        let mut result = self.source.clone();
+
+        for (range, replacement) in replacements.into_iter().rev() {
+            result.replace_range(range, replacement.as_str());
+        }
+
+        result
+    }
+
+    pub fn items(&self) -> &[Item] {
+        self.items.as_slice()
+    }
+
+    /**
+     * Writes the stylesheet without comments or optional whitespace.
+     */
+    pub fn minified(&self) -> String {
+        write_items(&self.items, None)
+    }
+
+    /**
+     * Writes the stylesheet with one declaration per line, indented with tabs.
+     * Comments are dropped.
+     */
+    pub fn pretty(&self) -> String {
+        write_items(&self.items, Some(0))
+    }
+}
+
+impl Rule {
+    pub fn selector(&self) -> &str {
+        self.selector.as_str()
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl AtRule {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn body(&self) -> &AtRuleBody {
+        &self.body
+    }
+}
+
+impl Declaration {
+    pub fn property(&self) -> &str {
+        self.property.as_str()
+    }
+
+    pub fn value(&self) -> &str {
+        self.value.as_str()
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+/**
+ * Calls the visitor for every declaration in document order, including those nested in at-rules.
+ */
+pub fn visit_declarations(items: &[Item], visitor: &mut impl FnMut(&Declaration)) {
+    for item in items {
+        let declarations = match item {
+            Item::Rule(rule) => rule.declarations.as_slice(),
+            Item::AtRule(at_rule) => match &at_rule.body {
+                AtRuleBody::Items(items) => {
+                    visit_declarations(items, visitor);
+                    continue;
+                }
+                AtRuleBody::Declarations(declarations) => declarations.as_slice(),
+                AtRuleBody::None => continue,
+            },
+        };
+
+        for declaration in declarations {
+            visitor(declaration);
+        }
+    }
+}
+
+/**
+ * Calls the visitor for every style rule in document order, including those nested in at-rules.
+ */
+fn visit_rules(items: &[Item], visitor: &mut impl FnMut(&Rule)) {
+    for item in items {
+        match item {
+            Item::Rule(rule) => visitor(rule),
+            Item::AtRule(AtRule {
+                body: AtRuleBody::Items(items),
+                ..
+            }) => visit_rules(items, visitor),
+            Item::AtRule(_) => {}
+        }
+    }
+}
+
+/**
+ * Writes items minified when no indentation depth is given, otherwise pretty-printed at that depth.
+ */
+fn write_items(items: &[Item], depth: Option<usize>) -> String {
+    let separator = match depth {
+        None => "",
+        Some(0) => "\n\n",
+        Some(_) => "\n",
+    };
+
+    items
+        .iter()
+        .map(|item| match item {
+            Item::Rule(rule) => write_block(
+                match depth {
+                    None => minify_selector(rule.selector.as_str()),
+                    Some(_) => rule.selector.clone(),
+                }
+                .as_str(),
+                write_declarations(&rule.declarations, depth),
+                depth,
+            ),
+            Item::AtRule(at_rule) => {
+                let head = match at_rule.prelude.is_empty() {
+                    true => format!("@{}", at_rule.name),
+                    false => format!("@{} {}", at_rule.name, at_rule.prelude),
+                };
+
+                match &at_rule.body {
+                    AtRuleBody::None => format!("{}{};", "\t".repeat(depth.unwrap_or(0)), head),
+                    AtRuleBody::Items(items) => write_block(
+                        head.as_str(),
+                        write_items(items, depth.map(|depth| depth + 1)),
+                        depth,
+                    ),
+                    AtRuleBody::Declarations(declarations) => write_block(
+                        head.as_str(),
+                        write_declarations(declarations, depth),
+                        depth,
+                    ),
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn write_block(head: &str, body: String, depth: Option<usize>) -> String {
+    let Some(depth) = depth else {
+        return format!("{}{{{}}}", head, body);
+    };
+
+    let indent = "\t".repeat(depth);
+
+    match body.is_empty() {
+        true => format!("{}{} {{}}", indent, head),
+        false => format!("{}{} {{\n{}\n{}}}", indent, head, body, indent),
+    }
+}
+
+fn write_declarations(declarations: &[Declaration], depth: Option<usize>) -> String {
+    match depth {
+        None => declarations
+            .iter()
+            .map(|declaration| format!("{}:{}", declaration.property, declaration.value))
+            .collect::<Vec<_>>()
+            .join(";"),
+        Some(depth) => declarations
+            .iter()
+            .map(|declaration| {
+                format!(
+                    "{}{}: {};",
+                    "\t".repeat(depth + 1),
+                    declaration.property,
+                    declaration.value
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/**
+ * Removes the spaces around selector list commas and combinators, outside of strings and brackets.
+ */
+fn minify_selector(selector: &str) -> String {
+    let chars = selector.chars().collect::<Vec<_>>();
+    let mut result = String::new();
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+
+    for (index, &c) in chars.iter().enumerate() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, ' ') if depth == 0 => {
+                let is_combinator = |c: Option<&char>| c.is_some_and(|c| ",>+~".contains(*c));
+
+                if is_combinator(index.checked_sub(1).and_then(|index| chars.get(index)))
+                    || is_combinator(chars.get(index + 1))
+                {
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        result.push(c);
+    }
+
+    result
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens[self.position..]
+            .iter()
+            .find(|token| !token.is_trivia())
+    }
+
+    /**
+     * Moves past trivia to the next meaningful token and returns it.
+     */
+    fn next(&mut self) -> Option<Token> {
+        while let Some(token) = self.tokens.get(self.position) {
+            self.position += 1;
+
+            if !token.is_trivia() {
+                return Some(token.clone());
+            }
+        }
+
+        None
+    }
+
+    fn text(&self, token: &Token) -> &str {
+        &self.source[token.range()]
+    }
+
+    /**
+     * Parses rules and at-rules until the end of the source, or until the closing brace
+     * of the block opened by the given token.
+     */
+    fn items(&mut self, block: Option<&Token>) -> Result<Vec<Item>> {
+        let mut items = Vec::new();
+
+        loop {
+            let Some(token) = self.peek().cloned() else {
+                return match block {
+                    Some(open) => Err(anyhow!("Line {}: Unclosed block.", open.line())),
+                    None => Ok(items),
+                };
+            };
+
+            match token.kind() {
+                TokenKind::CloseBrace if block.is_some() => {
+                    self.next();
+                    return Ok(items);
+                }
+                TokenKind::CloseBrace => {
+                    return Err(anyhow!("Line {}: Unexpected }}.", token.line()));
+                }
+                TokenKind::Semicolon => {
+                    self.next();
+                }
+                TokenKind::AtKeyword => items.push(Item::AtRule(self.at_rule()?)),
+                _ => items.push(Item::Rule(self.rule()?)),
+            }
+        }
+    }
+
+    fn at_rule(&mut self) -> Result<AtRule> {
+        let keyword = self.next().expect("At-rules start with a keyword.");
+        let name = self.text(&keyword)[1..].to_ascii_lowercase();
+
+        if name.is_empty() {
+            return Err(anyhow!("Line {}: Missing at-rule name.", keyword.line()));
+        }
+
+        let (prelude, end) = self.prelude(&keyword, true)?;
+
+        let body = match end.kind() {
+            TokenKind::OpenBrace if DECLARATION_AT_RULES.contains(&name.as_str()) => {
+                AtRuleBody::Declarations(self.declarations(Some(&end))?)
+            }
+            TokenKind::OpenBrace => AtRuleBody::Items(self.items(Some(&end))?),
+            _ => AtRuleBody::None,
+        };
+
+        Ok(AtRule {
+            name,
+            prelude,
+            line: keyword.line(),
+            body,
+        })
+    }
+
+    fn rule(&mut self) -> Result<Rule> {
+        let start = self.peek().cloned().expect("Rules start with a token.");
+        let (selector, open) = self.prelude(&start, false)?;
+
+        if selector.is_empty() {
+            return Err(anyhow!("Line {}: Missing selector.", open.line()));
+        }
+
+        Ok(Rule {
+            selector,
+            selector_range: start.range().start..open.range().start,
+            line: start.line(),
+            declarations: self.declarations(Some(&open))?,
+        })
+    }
+
+    /**
+     * Collects the text up to the `{` opening a block, or for at-rules also up to a `;`.
+     * Returns the text with comments removed and whitespace collapsed, and the token that ended it.
+     */
+    fn prelude(&mut self, start: &Token, is_at_rule: bool) -> Result<(String, Token)> {
+        let mut prelude = String::new();
+        let mut depth = 0;
+
+        loop {
+            let Some(token) = self.tokens.get(self.position).cloned() else {
+                return Err(anyhow!(
+                    "Line {}: Expected {{ after {}.",
+                    start.line(),
+                    prelude.trim()
+                ));
+            };
+
+            self.position += 1;
+
+            match token.kind() {
+                TokenKind::OpenParen | TokenKind::OpenBracket => depth += 1,
+                TokenKind::CloseParen | TokenKind::CloseBracket if depth == 0 => {
+                    return Err(anyhow!(
+                        "Line {}: Unbalanced {}.",
+                        token.line(),
+                        self.text(&token)
+                    ));
+                }
+                TokenKind::CloseParen | TokenKind::CloseBracket => depth -= 1,
+                TokenKind::OpenBrace if depth == 0 => {
+                    return Ok((prelude.trim().to_string(), token));
+                }
+                TokenKind::Semicolon if depth == 0 && is_at_rule => {
+                    return Ok((prelude.trim().to_string(), token));
+                }
+                TokenKind::Semicolon | TokenKind::CloseBrace if depth == 0 => {
+                    return Err(anyhow!(
+                        "Line {}: Expected {{ after {}.",
+                        token.line(),
+                        prelude.trim()
+                    ));
+                }
+                _ => {}
+            }
+
+            match token.kind() {
+                TokenKind::Comment => {}
+                TokenKind::Whitespace => {
+                    if !prelude.is_empty() && !prelude.ends_with(' ') {
+                        prelude.push(' ');
+                    }
+                }
+                _ => prelude.push_str(self.text(&token)),
+            }
+        }
+    }
+
+    /**
+     * Parses declarations up to the closing brace of the block opened by the given token.
+     */
+    fn declarations(&mut self, open: Option<&Token>) -> Result<Vec<Declaration>> {
+        let mut declarations = Vec::new();
+
+        loop {
+            let Some(token) = self.next() else {
+                return match open {
+                    Some(open) => Err(anyhow!("Line {}: Unclosed block.", open.line())),
+                    None => Ok(declarations),
+                };
+            };
+
+            let property = match token.kind() {
+                TokenKind::CloseBrace if open.is_some() => return Ok(declarations),
+                TokenKind::Semicolon => continue,
+                TokenKind::Word => self.text(&token).to_string(),
+                _ => {
+                    return Err(anyhow!(
+                        "Line {}: Expected a property name, found {}.",
+                        token.line(),
+                        self.text(&token)
+                    ))
+                }
+            };
+
+            match self.next() {
+                Some(colon) if colon.kind() == TokenKind::Colon => {}
+                _ => {
+                    return Err(anyhow!(
+                        "Line {}: Expected : after {}.",
+                        token.line(),
+                        property
+                    ))
+                }
+            }
+
+            let mut value_range: Option<Range<usize>> = None;
+            let mut depth = 0;
+
+            let closed = loop {
+                let Some(value_token) = self.tokens.get(self.position).cloned() else {
+                    match open {
+                        Some(open) => return Err(anyhow!("Line {}: Unclosed block.", open.line())),
+                        None if depth > 0 => {
+                            return Err(anyhow!("Line {}: Unclosed (.", token.line()))
+                        }
+                        None => break true,
+                    }
+                };
+
+                match value_token.kind() {
+                    TokenKind::Semicolon if depth == 0 => {
+                        self.position += 1;
+                        break false;
+                    }
+                    TokenKind::CloseBrace if depth == 0 && open.is_some() => break true,
+                    TokenKind::CloseBrace if depth == 0 => {
+                        return Err(anyhow!("Line {}: Unexpected }}.", value_token.line()));
+                    }
+                    TokenKind::OpenBrace => {
+                        return Err(anyhow!(
+                            "Line {}: Nested rules are not supported.",
+                            value_token.line()
+                        ));
+                    }
+                    TokenKind::OpenParen | TokenKind::OpenBracket => depth += 1,
+                    TokenKind::CloseParen | TokenKind::CloseBracket if depth == 0 => {
+                        return Err(anyhow!(
+                            "Line {}: Unbalanced {}.",
+                            value_token.line(),
+                            self.text(&value_token)
+                        ));
+                    }
+                    TokenKind::CloseParen | TokenKind::CloseBracket => depth -= 1,
+                    _ => {}
+                }
+
+                self.position += 1;
+
+                if !value_token.is_trivia() {
+                    let range = value_token.range();
+
+                    value_range = Some(match value_range {
+                        Some(value_range) => value_range.start..range.end,
+                        None => range,
+                    });
+                }
+            };
+
+            let Some(value_range) = value_range else {
+                return Err(anyhow!(
+                    "Line {}: Missing value for {}.",
+                    token.line(),
+                    property
+                ));
+            };
+
+            declarations.push(Declaration {
+                property: match property.starts_with("--") {
+                    true => property,
+                    false => property.to_ascii_lowercase(),
+                },
+                value: self.source[value_range.clone()].to_string(),
+                value_range,
+                line: token.line(),
+            });
+
+            if closed {
+                self.next();
+                return Ok(declarations);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AtRuleBody, Item, Stylesheet};
+
+    const SOURCE: &str = r#"@import url("theme.css");
+/* pipes */
+.cls-1, .cls-2 > path {
+    fill: #FF0000;
+    Stroke: url(data:image/png;base64,AA==) !important
+}
+
+@media (min-width: 10px) {
+    text { font-family: 'A;B', serif; }
+}
+
+@font-face { font-family: Inter; src: url(inter.woff2) }
+"#;
+
+    #[test]
+    fn test_parse_stylesheet() {
+        let stylesheet = Stylesheet::parse(SOURCE).unwrap();
+        let items = &stylesheet.items;
+
+        let Item::AtRule(import) = &items[0] else {
+            panic!("Expected @import.");
+        };
+
+        assert_eq!(
+            (import.name.as_str(), import.prelude.as_str()),
+            ("import", r#"url("theme.css")"#)
+        );
+        assert_eq!(import.body, AtRuleBody::None);
+
+        let Item::Rule(rule) = &items[1] else {
+            panic!("Expected a rule.");
+        };
+
+        assert_eq!(rule.selector, ".cls-1, .cls-2 > path");
+        assert_eq!(rule.line, 3);
+
+        let declarations = rule
+            .declarations
+            .iter()
+            .map(|declaration| {
+                (
+                    declaration.property(),
+                    declaration.value(),
+                    declaration.line,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            declarations,
+            vec![
+                ("fill", "#FF0000", 4),
+                ("stroke", "url(data:image/png;base64,AA==) !important", 5)
+            ]
+        );
+
+        assert!(matches!(
+            &items[2],
+            Item::AtRule(media) if matches!(&media.body, AtRuleBody::Items(items) if items.len() == 1)
+        ));
+        assert!(matches!(
+            &items[3],
+            Item::AtRule(font_face) if matches!(&font_face.body, AtRuleBody::Declarations(declarations) if declarations.len() == 2)
+        ));
+    }
+
+    #[test]
+    fn test_map_values_keeps_formatting() {
+        let stylesheet = Stylesheet::parse(SOURCE).unwrap();
+        let result = stylesheet.map_values(|declaration| {
+            (declaration.value() == "#FF0000").then(|| "var(--red)".to_string())
+        });
+
+        assert_eq!(result, SOURCE.replace("#FF0000", "var(--red)"));
+    }
+
+    #[test]
+    fn test_map_selector_ids() {
+        let source = "#a, g#a > .b:not(#c) /* #a */ { fill: #a }\n@media print { [href='#a'] #a-b, #ab { } }";
+        let stylesheet = Stylesheet::parse(source).unwrap();
+        let mut ids = Vec::new();
+
+        let result = stylesheet.map_selector_ids(|id| {
+            ids.push(id.to_string());
+            (id == "a").then(|| r#"[data-id="a"]"#.to_string())
+        });
+
+        assert_eq!(ids, vec!["a", "a", "c", "a-b", "ab"]);
+        assert_eq!(
+            result,
+            "[data-id=\"a\"], g[data-id=\"a\"] > .b:not(#c) /* #a */ { fill: #a }\n@media print { [href='#a'] #a-b, #ab { } }"
+        );
+    }
+
+    #[test]
+    fn test_minified_and_pretty() {
+        let stylesheet = Stylesheet::parse(SOURCE).unwrap();
+
+        assert_eq!(
+            stylesheet.minified(),
+            r#"@import url("theme.css");.cls-1,.cls-2>path{fill:#FF0000;stroke:url(data:image/png;base64,AA==) !important}@media (min-width: 10px){text{font-family:'A;B', serif}}@font-face{font-family:Inter;src:url(inter.woff2)}"#
+        );
+        assert_eq!(
+            stylesheet.pretty(),
+            r#"@import url("theme.css");
+
+.cls-1, .cls-2 > path {
+	fill: #FF0000;
+	stroke: url(data:image/png;base64,AA==) !important;
+}
+
+@media (min-width: 10px) {
+	text {
+		font-family: 'A;B', serif;
+	}
+}
+
+@font-face {
+	font-family: Inter;
+	src: url(inter.woff2);
+}"#
+        );
+        assert_eq!(
+            Stylesheet::parse(&stylesheet.pretty()).unwrap().minified(),
+            stylesheet.minified()
+        );
+    }
+
+    #[test]
+    fn test_parse_inline() {
+        let declarations = Stylesheet::parse_inline("fill:#000; stroke-width : 2 ;").unwrap();
+
+        assert_eq!(declarations.len(), 2);
+        assert_eq!(
+            (declarations[1].property(), declarations[1].value()),
+            ("stroke-width", "2")
+        );
+        assert!(Stylesheet::parse_inline("fill: url(#a").is_err());
+        assert!(Stylesheet::parse_inline("fill: red }").is_err());
+    }
+
+    #[test]
+    fn test_parse_errors_have_line_numbers() {
+        let errors = [
+            ("a { fill: red;\n", "Line 1: Unclosed block."),
+            ("a { fill: red; }\n}", "Line 2: Unexpected }."),
+            ("a {\n fill red; }", "Line 2: Expected : after fill."),
+            ("a {\n fill: ; }", "Line 2: Missing value for fill."),
+            ("\n\na, b;", "Line 3: Expected { after a, b."),
+            ("a { fill: url(x; }", "Line 1: Unclosed block."),
+            (
+                "a { &:hover { fill: red } }",
+                "Line 1: Nested rules are not supported.",
+            ),
+        ];
+
+        for (source, error) in errors {
+            assert_eq!(
+                Stylesheet::parse(source).unwrap_err().to_string(),
+                error,
+                "{}",
+                source
+            );
+        }
+    }
+}
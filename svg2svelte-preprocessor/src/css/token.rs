@@ -0,0 +1,211 @@
+use anyhow::{anyhow, Result};
+use std::ops::Range;
+
+/**
+ * The kinds of tokens the stylesheet parser distinguishes. Everything that is not
+ * structural, such as selectors, property names and values, is a word.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    Comment,
+    String,
+    AtKeyword,
+    Word,
+    Colon,
+    Semicolon,
+    OpenBrace,
+    CloseBrace,
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+}
+
+/**
+ * A token and where it is in the source, with its 1-based line.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    kind: TokenKind,
+    range: Range<usize>,
+    line: usize,
+}
+
+impl Token {
+    pub fn kind(&self) -> TokenKind {
+        self.kind
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /**
+     * Whitespace and comments, which separate tokens but carry no meaning.
+     */
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind, TokenKind::Whitespace | TokenKind::Comment)
+    }
+}
+
+/**
+ * Splits CSS source into tokens. Strings and comments are kept whole, and a backslash
+ * escapes the character after it, so `\{` is part of a word.
+ */
+pub fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    let mut line = 1;
+
+    while let Some((start, c)) = chars.next() {
+        let token_line = line;
+
+        let kind = match c {
+            '{' => TokenKind::OpenBrace,
+            '}' => TokenKind::CloseBrace,
+            '(' => TokenKind::OpenParen,
+            ')' => TokenKind::CloseParen,
+            '[' => TokenKind::OpenBracket,
+            ']' => TokenKind::CloseBracket,
+            ':' => TokenKind::Colon,
+            ';' => TokenKind::Semicolon,
+            c if c.is_whitespace() => {
+                line += (c == '\n') as usize;
+
+                while let Some(&(_, c)) = chars.peek().filter(|(_, c)| c.is_whitespace()) {
+                    line += (c == '\n') as usize;
+                    chars.next();
+                }
+
+                TokenKind::Whitespace
+            }
+            '/' if chars.peek().is_some_and(|&(_, c)| c == '*') => {
+                chars.next();
+
+                let mut previous = ' ';
+                let mut terminated = false;
+
+                for (_, c) in chars.by_ref() {
+                    line += (c == '\n') as usize;
+
+                    if previous == '*' && c == '/' {
+                        terminated = true;
+                        break;
+                    }
+
+                    previous = c;
+                }
+
+                if !terminated {
+                    return Err(anyhow!("Line {}: Unterminated comment.", token_line));
+                }
+
+                TokenKind::Comment
+            }
+            '"' | '\'' => {
+                let quote = c;
+                let mut terminated = false;
+
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => line += chars.next().is_some_and(|(_, c)| c == '\n') as usize,
+                        '\n' => break,
+                        c if c == quote => {
+                            terminated = true;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+
+                if !terminated {
+                    return Err(anyhow!("Line {}: Unterminated string.", token_line));
+                }
+
+                TokenKind::String
+            }
+            _ => {
+                let mut escaped = c == '\\';
+
+                while let Some(&(index, c)) = chars.peek() {
+                    let ends_word = c.is_whitespace()
+                        || "{}()[]:;\"'".contains(c)
+                        || source[index..].starts_with("/*");
+
+                    if !escaped && ends_word {
+                        break;
+                    }
+
+                    line += (c == '\n') as usize;
+                    escaped = !escaped && c == '\\';
+                    chars.next();
+                }
+
+                match c {
+                    '@' => TokenKind::AtKeyword,
+                    _ => TokenKind::Word,
+                }
+            }
+        };
+
+        let end = chars.peek().map_or(source.len(), |&(end, _)| end);
+
+        tokens.push(Token {
+            kind,
+            range: start..end,
+            line: token_line,
+        });
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{tokenize, TokenKind};
+
+    #[test]
+    fn test_tokenize() {
+        let source = "@media (x) {\n\t.a\\:b { fill: url('#a;b') } /* c\n */ }";
+        let tokens = tokenize(source).unwrap();
+        let kinds = tokens
+            .iter()
+            .filter(|token| !token.is_trivia())
+            .map(|token| (token.kind(), &source[token.range()], token.line()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (TokenKind::AtKeyword, "@media", 1),
+                (TokenKind::OpenParen, "(", 1),
+                (TokenKind::Word, "x", 1),
+                (TokenKind::CloseParen, ")", 1),
+                (TokenKind::OpenBrace, "{", 1),
+                (TokenKind::Word, ".a\\:b", 2),
+                (TokenKind::OpenBrace, "{", 2),
+                (TokenKind::Word, "fill", 2),
+                (TokenKind::Colon, ":", 2),
+                (TokenKind::Word, "url", 2),
+                (TokenKind::OpenParen, "(", 2),
+                (TokenKind::String, "'#a;b'", 2),
+                (TokenKind::CloseParen, ")", 2),
+                (TokenKind::CloseBrace, "}", 2),
+                (TokenKind::CloseBrace, "}", 3),
+            ]
+        );
+        assert_eq!(
+            tokenize("a {}\n/* open").unwrap_err().to_string(),
+            "Line 2: Unterminated comment."
+        );
+        assert_eq!(
+            tokenize("a { content: 'x }\n").unwrap_err().to_string(),
+            "Line 1: Unterminated string."
+        );
+    }
+}
@@ -0,0 +1,374 @@
+use super::stylesheet::{AtRuleBody, Item, Rule, Stylesheet};
+use crate::{
+    svelte::target::SvelteTarget,
+    svg::{document::Document, selector::Selector},
+};
+use std::fmt::Display;
+
+/**
+ * A problem in the style file that does not stop generation.
+ */
+#[derive(Debug, PartialEq)]
+pub struct StyleWarning {
+    line: usize,
+    message: String,
+}
+
+impl Display for StyleWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+/**
+ * Checks the rules of a stylesheet against the SVG they are embedded with.
+ *
+ * Selectors that match no element are reported as dead. Matching is loose: pseudo-classes,
+ * attribute selectors and sibling combinators are ignored, so a reported selector can never
+ * match, while selectors the matcher does not understand are assumed to match.
+ * Selectors using `:global` are not matched, as they may target markup outside the component,
+ * but are checked for the mistakes Svelte rejects or that leak styles to the whole page.
+ */
+pub fn validate(
+    stylesheet: &Stylesheet,
+    document: &Document,
+    target: SvelteTarget,
+) -> Vec<StyleWarning> {
+    let mut warnings = Vec::new();
+
+    visit_rules(stylesheet.items(), &mut |rule| {
+        for selector in split_top_level(rule.selector(), ',') {
+            let compounds = compounds(selector);
+
+            let messages = match selector.contains(":global") {
+                true => global_messages(selector, &compounds, target),
+                false => match matches_any(&compounds, document) {
+                    true => Vec::new(),
+                    false => vec![format!(
+                        "Selector `{}` matches nothing in the SVG.",
+                        selector
+                    )],
+                },
+            };
+
+            warnings.extend(messages.into_iter().map(|message| StyleWarning {
+                line: rule.line(),
+                message,
+            }));
+        }
+    });
+
+    warnings
+}
+
+/**
+ * Calls the visitor for every style rule, including those in conditional at-rules.
+ * Keyframe selectors such as `from` are not element selectors, so keyframes are skipped.
+ */
+fn visit_rules(items: &[Item], visitor: &mut impl FnMut(&Rule)) {
+    for item in items {
+        match item {
+            Item::Rule(rule) => visitor(rule),
+            Item::AtRule(at_rule) if !at_rule.name().ends_with("keyframes") => {
+                if let AtRuleBody::Items(items) = at_rule.body() {
+                    visit_rules(items, visitor);
+                }
+            }
+            Item::AtRule(_) => {}
+        }
+    }
+}
+
+fn global_messages(
+    selector: &str,
+    compounds: &[(char, &str)],
+    target: SvelteTarget,
+) -> Vec<String> {
+    let mut messages = Vec::new();
+
+    for (index, (_, compound)) in compounds.iter().enumerate() {
+        let mut rest = *compound;
+
+        while let Some(start) = rest.find(":global") {
+            rest = &rest[start + ":global".len()..];
+
+            let Some(arguments) = rest.strip_prefix('(') else {
+                match target {
+                    SvelteTarget::Svelte4 => messages.push(format!(
+                        "Bare `:global` in `{}` needs Svelte 5, use `:global(...)` instead.",
+                        selector
+                    )),
+                    SvelteTarget::Svelte5 if *compound != ":global" => messages.push(format!(
+                        "Bare `:global` in `{}` must stand alone, as in `:global .name`.",
+                        selector
+                    )),
+                    SvelteTarget::Svelte5 => {}
+                }
+
+                continue;
+            };
+
+            let inner = &arguments[..closing_paren(arguments).unwrap_or(arguments.len())];
+
+            if inner.trim().is_empty() || split_top_level(inner, ',').len() > 1 {
+                messages.push(format!(
+                    "`:global(...)` in `{}` must contain a single selector.",
+                    selector
+                ));
+            }
+
+            if index > 0 && index + 1 < compounds.len() {
+                messages.push(format!(
+                    "`:global(...)` in `{}` can only be at the start or end of a selector.",
+                    selector
+                ));
+            }
+        }
+    }
+
+    let is_global = |compound: &&str| {
+        compound.starts_with(":global(")
+            && closing_paren(&compound[":global(".len()..])
+                .is_some_and(|end| ":global(".len() + end + 1 == compound.len())
+    };
+
+    let is_unscoped = compounds
+        .first()
+        .is_some_and(|(_, first)| *first == ":global")
+        || compounds.iter().all(|(_, compound)| is_global(compound));
+
+    if messages.is_empty() && is_unscoped {
+        messages.push(format!(
+            "`{}` is not scoped to the component and styles the whole page, start it with a scoped selector such as `.diagram`.",
+            selector
+        ));
+    }
+
+    messages
+}
+
+/**
+ * Whether any element could match the selector. Sibling combinators only narrow the element
+ * matched after them, so everything before the last one is dropped, and child combinators
+ * are treated as descendant combinators.
+ */
+fn matches_any(compounds: &[(char, &str)], document: &Document) -> bool {
+    let start = compounds
+        .iter()
+        .rposition(|(combinator, _)| matches!(combinator, '+' | '~'))
+        .unwrap_or(0);
+
+    let Some(simple) = compounds[start..]
+        .iter()
+        .map(|(_, compound)| simple_compound(compound))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return true;
+    };
+
+    let Ok(selector) = Selector::new(simple.join(" ").as_str()) else {
+        return true;
+    };
+
+    let mut found = false;
+
+    document.root().walk(&mut |element, ancestors| {
+        found = found || selector.matches(element, ancestors);
+    });
+
+    found
+}
+
+/**
+ * A compound without pseudo-classes, pseudo-elements and attribute selectors,
+ * or `None` when it uses escapes.
+ */
+fn simple_compound(compound: &str) -> Option<String> {
+    let mut simple = String::new();
+    let mut chars = compound.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => return None,
+            ':' => {
+                while chars.next_if(|&c| c == ':').is_some() {}
+                while chars
+                    .next_if(|&c| c.is_alphanumeric() || matches!(c, '-' | '_'))
+                    .is_some()
+                {}
+
+                if chars.next_if(|&c| c == '(').is_some() {
+                    skip_balanced(&mut chars, '(', ')');
+                }
+            }
+            '[' => skip_balanced(&mut chars, '[', ']'),
+            c => simple.push(c),
+        }
+    }
+
+    match simple.is_empty() {
+        true => Some("*".to_string()),
+        false => Some(simple),
+    }
+}
+
+fn skip_balanced(chars: &mut impl Iterator<Item = char>, open: char, close: char) {
+    let mut depth = 1;
+
+    for c in chars {
+        depth += (c == open) as usize;
+        depth -= (c == close) as usize;
+
+        if depth == 0 {
+            break;
+        }
+    }
+}
+
+/**
+ * Splits a complex selector into its compounds, each with the combinator before it.
+ * The first compound and descendants have a space as their combinator.
+ */
+fn compounds(selector: &str) -> Vec<(char, &str)> {
+    let mut compounds = Vec::new();
+    let mut combinator = ' ';
+    let mut start = None;
+    let mut depth = 0;
+
+    for (index, c) in selector.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            c if depth == 0 && (c.is_whitespace() || matches!(c, '>' | '+' | '~')) => {
+                if let Some(start) = start.take() {
+                    compounds.push((combinator, &selector[start..index]));
+                    combinator = ' ';
+                }
+
+                if !c.is_whitespace() {
+                    combinator = c;
+                }
+
+                continue;
+            }
+            _ => {}
+        }
+
+        start.get_or_insert(index);
+    }
+
+    if let Some(start) = start {
+        compounds.push((combinator, &selector[start..]));
+    }
+
+    compounds
+}
+
+/**
+ * Splits text on a separator outside of parentheses, brackets and strings.
+ */
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, c) if c == separator && depth == 0 => {
+                parts.push(text[start..index].trim());
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(text[start..].trim());
+    parts
+}
+
+/**
+ * The index of the parenthesis closing an already opened one.
+ */
+fn closing_paren(text: &str) -> Option<usize> {
+    let mut depth = 1;
+
+    text.char_indices().find_map(|(index, c)| {
+        depth += (c == '(') as usize;
+        depth -= (c == ')') as usize;
+        (depth == 0).then_some(index)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::validate;
+    use crate::{
+        css::stylesheet::Stylesheet, svelte::target::SvelteTarget, svg::document::Document,
+    };
+
+    const SVG: &str =
+        r#"<svg class="diagram"><g class="pipe"><path id="valve-1"/></g><text/></svg>"#;
+
+    fn warnings(source: &str, target: SvelteTarget) -> Vec<String> {
+        let stylesheet = Stylesheet::parse(source).unwrap();
+        let document = Document::parse(SVG).unwrap();
+
+        validate(&stylesheet, &document, target)
+            .iter()
+            .map(|warning| warning.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_dead_selectors() {
+        let source = r#".pipe path:hover, .missing { fill: red; }
+g > #valve-1, rect + path, text::before, [data-x] {}
+@media (min-width: 10px) {
+    .diagram .pipe text {}
+}
+@keyframes spin { from { opacity: 0 } to { opacity: 1 } }
+.a\:b {}"#;
+
+        assert_eq!(
+            warnings(source, SvelteTarget::Svelte4),
+            vec![
+                "Line 1: Selector `.missing` matches nothing in the SVG.",
+                "Line 4: Selector `.diagram .pipe text` matches nothing in the SVG.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_global_misuse() {
+        let source = r#":global(.a, .b) {}
+.pipe :global(path) .x {}
+:global(body) {}
+.diagram :global(.tooltip) {}
+:global .x {}
+.diagram:global {}"#;
+
+        assert_eq!(
+            warnings(source, SvelteTarget::Svelte4),
+            vec![
+                "Line 1: `:global(...)` in `:global(.a, .b)` must contain a single selector.",
+                "Line 2: `:global(...)` in `.pipe :global(path) .x` can only be at the start or end of a selector.",
+                "Line 3: `:global(body)` is not scoped to the component and styles the whole page, start it with a scoped selector such as `.diagram`.",
+                "Line 5: Bare `:global` in `:global .x` needs Svelte 5, use `:global(...)` instead.",
+                "Line 6: Bare `:global` in `.diagram:global` needs Svelte 5, use `:global(...)` instead.",
+            ]
+        );
+        assert_eq!(
+            warnings(":global .x {}\n.diagram:global {}", SvelteTarget::Svelte5),
+            vec![
+                "Line 1: `:global .x` is not scoped to the component and styles the whole page, start it with a scoped selector such as `.diagram`.",
+                "Line 2: Bare `:global` in `.diagram:global` must stand alone, as in `:global .name`.",
+            ]
+        );
+    }
+}
@@ -0,0 +1,6 @@
+pub mod audit;
+pub mod config;
+pub mod css;
+pub mod svelte;
+pub mod svg;
+pub mod theme;
@@ -1,14 +1,30 @@
-mod config;
 mod options;
-mod svelte;
-mod svg;
 
 use anyhow::Result;
-use config::Config;
 use options::Options;
 use std::io::Write;
-use svelte::transpiler::Transpiler;
-use svg::{css_color::CssColor, modifier::Modifier, parser::Parser, var_color::VarColor};
+use svg2svelte_preprocessor::{
+    audit,
+    config::Config,
+    css::{
+        inline_style::InlineStyles, style_format::StyleFormat, stylesheet::Stylesheet, validator,
+    },
+    svelte::{
+        component::Component, kind::ComponentKind, manifest::Manifest, target::SvelteTarget,
+        transpiler::Transpiler,
+    },
+    svg::{
+        accessibility::{Accessibility, DiagramRole},
+        color_mapping::{ColorMapping, PaintProperty},
+        css_color::CssColor,
+        document::{Document, Element},
+        geometry::BoundingBox,
+        modifier::{map_stylesheet, Modifier},
+        selector::Selector,
+        token::{TokenMapping, TokenProperty},
+        var_color::VarColor,
+    },
+};
 
 macro_rules! readln {
     ($input:expr) => {
@@ -17,6 +33,13 @@ macro_rules! readln {
     };
 }
 
+macro_rules! pause {
+    () => {
+        print!("Press enter to continue...");
+        readln!(&mut String::new());
+    };
+}
+
 macro_rules! clear {
     () => {
         print!("\x1B[2J\x1B[H");
@@ -43,6 +66,14 @@ fn main() -> Result<()> {
         }
     };
 
+    let arguments: Vec<String> = std::env::args().skip(1).collect();
+
+    if let [command, id] = arguments.as_slice() {
+        if command == "bbox" {
+            return print_bounding_box(&config, id.as_str());
+        }
+    }
+
     loop {
         println!("{}\n{}\n", config, Options);
         print!("Select an option: ");
@@ -62,14 +93,29 @@ fn main() -> Result<()> {
                     }
                 };
 
-                let (attributes, original_svg_tag_text) =
-                    Parser::parse_attributes(raw_svg_text.as_str())?;
-                let mut modifier = Modifier::new(&config, raw_svg_text.as_str());
+                let document = match Document::parse(raw_svg_text.as_str()) {
+                    Ok(document) => document,
+                    Err(error) => {
+                        println!("Failed to parse SVG file: {}", error);
+                        continue;
+                    }
+                };
+
+                let mut modifier = Modifier::new(&config, document);
 
-                let finalized_svg_text = modifier
-                    .fix_svg_open_tag(original_svg_tag_text.as_str(), attributes)
-                    .map_colors()
-                    .accumulate();
+                match modifier.run() {
+                    Ok(reports) => {
+                        for report in reports {
+                            println!("{}", report);
+                        }
+                    }
+                    Err(error) => {
+                        println!("Failed to transform SVG: {}", error);
+                        continue;
+                    }
+                }
+
+                let finalized_document = modifier.accumulate();
 
                 let raw_style_text = match config.read_style_file() {
                     Ok(text) => text,
@@ -79,13 +125,88 @@ fn main() -> Result<()> {
                     }
                 };
 
-                let raw_svelte_text =
-                    Transpiler::to_svelte(finalized_svg_text, raw_style_text.as_str());
+                let stylesheet = match Stylesheet::parse(raw_style_text.as_str()) {
+                    Ok(stylesheet) => stylesheet,
+                    Err(error) => {
+                        println!("Failed to parse style file: {}", error);
+                        continue;
+                    }
+                };
+
+                for warning in
+                    validator::validate(&stylesheet, finalized_document, config.svelte_target())
+                {
+                    println!("Style file: {}", warning);
+                }
+
+                let mapped_style_text = map_stylesheet(&config, &stylesheet);
+
+                let unmapped_reports = [
+                    audit::unmapped::audit_document(finalized_document),
+                    match Stylesheet::parse(mapped_style_text.as_str()) {
+                        Ok(mapped_stylesheet) => {
+                            audit::unmapped::audit_stylesheet("the style file", &mapped_stylesheet)
+                        }
+                        Err(error) => {
+                            println!("Failed to parse mapped style file: {}", error);
+                            continue;
+                        }
+                    },
+                ];
+
+                for report in unmapped_reports.iter().filter(|report| !report.is_empty()) {
+                    print!("{}", report);
+                }
+
+                let style_text = [modifier.extracted_style(), mapped_style_text.trim()]
+                    .into_iter()
+                    .filter(|style| !style.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+
+                let style_text = match config.style_format().apply(style_text.as_str()) {
+                    Ok(style_text) => style_text,
+                    Err(error) => {
+                        println!("Failed to format style: {}", error);
+                        continue;
+                    }
+                };
+
+                let component = match Component::new(
+                    &config,
+                    finalized_document,
+                    style_text.as_str(),
+                    modifier.assets(),
+                ) {
+                    Ok(component) => component,
+                    Err(error) => {
+                        println!("Failed to generate Svelte component: {}", error);
+                        continue;
+                    }
+                };
+
+                let raw_svelte_text = Transpiler::to_svelte(config.svelte_target(), &component);
+                let manifest = Manifest::new(
+                    config.component_name().as_str(),
+                    config.svg_file_name().as_str(),
+                    &component,
+                );
 
                 config
                     .output_file(raw_svelte_text.as_str())
                     .expect("Failed to write output file.");
 
+                config
+                    .output_manifest(
+                        manifest.to_json().as_str(),
+                        manifest.to_declaration(&component).as_str(),
+                    )
+                    .expect("Failed to write manifest files.");
+
+                config
+                    .output_assets(modifier.assets())
+                    .expect("Failed to write asset files.");
+
                 println!("Successfully processed SVG file.");
             }
             Some(2) => {
@@ -114,7 +235,38 @@ fn main() -> Result<()> {
                     }
                 };
 
-                config.set_color_mapping(css_color, var_color);
+                let mut property_text = String::new();
+                let mut selector_text = String::new();
+
+                print!("Enter property (fill, stroke, stop-color or empty for any): ");
+                readln!(&mut property_text);
+
+                let mut color_mapping = ColorMapping::new(css_color, var_color);
+
+                if !property_text.trim().is_empty() {
+                    match PaintProperty::new(property_text.trim()) {
+                        Ok(property) => color_mapping = color_mapping.with_property(property),
+                        Err(_) => {
+                            println!("Unsupported property provided. Please try again.");
+                            continue;
+                        }
+                    }
+                }
+
+                print!("Enter element selector (or empty for any): ");
+                readln!(&mut selector_text);
+
+                if !selector_text.trim().is_empty() {
+                    match Selector::new(selector_text.trim()) {
+                        Ok(selector) => color_mapping = color_mapping.with_selector(selector),
+                        Err(_) => {
+                            println!("Invalid selector provided. Please try again.");
+                            continue;
+                        }
+                    }
+                }
+
+                config.set_color_mapping(color_mapping);
             }
             Some(3) => {
                 let mut svg_file_path = String::new();
@@ -146,7 +298,260 @@ fn main() -> Result<()> {
 
                 println!("Output file path successfully set.");
             }
-            Some(6) => break,
+            Some(6) => {
+                let mut theme_name = String::new();
+                let mut var_color_text = String::new();
+                let mut css_color_text = String::new();
+
+                print!("Enter theme name: ");
+                readln!(&mut theme_name);
+
+                print!("Enter variable color: ");
+                readln!(&mut var_color_text);
+
+                let var_color = match VarColor::new(var_color_text.trim()) {
+                    Ok(color) => color,
+                    Err(_) => {
+                        println!("Invalid variable color provided. Please try again.");
+                        continue;
+                    }
+                };
+
+                print!("Enter CSS color: ");
+                readln!(&mut css_color_text);
+
+                let css_color = match CssColor::new(css_color_text.trim()) {
+                    Ok(color) => color,
+                    Err(_) => {
+                        println!("Invalid CSS color provided. Please try again.");
+                        continue;
+                    }
+                };
+
+                config.set_theme_color(theme_name.trim(), var_color, css_color);
+            }
+            Some(7) => {
+                let raw_svg_text = match config.read_svg_file() {
+                    Ok(text) => text,
+                    Err(_) => {
+                        println!("Failed to read SVG file. Please try again.");
+                        continue;
+                    }
+                };
+
+                let document = match Document::parse(raw_svg_text.as_str()) {
+                    Ok(document) => document,
+                    Err(error) => {
+                        println!("Failed to parse SVG file: {}", error);
+                        continue;
+                    }
+                };
+
+                for report in audit::contrast::audit(&config, &document) {
+                    println!("{}", report);
+                }
+
+                pause!();
+            }
+            Some(8) => {
+                for report in audit::cvd::audit(&config) {
+                    println!("{}", report);
+                }
+
+                pause!();
+            }
+            Some(9) => {
+                let mut property_text = String::new();
+                let mut value_text = String::new();
+                let mut var_color_text = String::new();
+
+                print!("Enter property (stroke-width, opacity, font-size, font-family, stroke-dasharray): ");
+                readln!(&mut property_text);
+
+                let property = match TokenProperty::new(property_text.trim()) {
+                    Ok(property) => property,
+                    Err(_) => {
+                        println!("Unsupported property provided. Please try again.");
+                        continue;
+                    }
+                };
+
+                print!("Enter {} value: ", property);
+                readln!(&mut value_text);
+
+                print!("Enter variable name: ");
+                readln!(&mut var_color_text);
+
+                let var_color = match VarColor::new(var_color_text.trim()) {
+                    Ok(color) => color,
+                    Err(_) => {
+                        println!("Invalid variable name provided. Please try again.");
+                        continue;
+                    }
+                };
+
+                match TokenMapping::new(property, value_text.as_str(), var_color) {
+                    Ok(mapping) => config.set_token_mapping(mapping),
+                    Err(_) => {
+                        println!("Invalid {} value provided. Please try again.", property);
+                        continue;
+                    }
+                }
+            }
+            Some(10) => {
+                config.set_generate_script(!config.generate_script());
+
+                println!(
+                    "Script generation {}.",
+                    if config.generate_script() {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+            }
+            Some(11) => {
+                let mut target_text = String::new();
+
+                print!("Enter Svelte target (svelte4 or svelte5): ");
+                readln!(&mut target_text);
+
+                match SvelteTarget::new(target_text.trim()) {
+                    Ok(target) => config.set_svelte_target(target),
+                    Err(_) => {
+                        println!("Unsupported Svelte target provided. Please try again.");
+                        continue;
+                    }
+                }
+            }
+            Some(12) => {
+                let mut kind_text = String::new();
+                let mut id_pattern_text = String::new();
+                let mut states_text = String::new();
+
+                print!("Enter component kind (for example valve): ");
+                readln!(&mut kind_text);
+
+                print!("Enter layer id pattern (for example valve-*): ");
+                readln!(&mut id_pattern_text);
+
+                print!("Enter states (for example open=--valve-open, closed=--valve-closed): ");
+                readln!(&mut states_text);
+
+                let component_kind =
+                    ComponentKind::parse_states(states_text.as_str()).and_then(|states| {
+                        ComponentKind::new(kind_text.trim(), id_pattern_text.trim(), states)
+                    });
+
+                match component_kind {
+                    Ok(component_kind) => config.set_component_kind(component_kind),
+                    Err(error) => {
+                        println!("{} Please try again.", error);
+                        continue;
+                    }
+                }
+            }
+            Some(13) => {
+                let mut title_text = String::new();
+                let mut description_text = String::new();
+                let mut role_text = String::new();
+                let mut labelled_ids_text = String::new();
+
+                print!("Enter diagram title (empty for none): ");
+                readln!(&mut title_text);
+
+                print!("Enter diagram description (empty for none): ");
+                readln!(&mut description_text);
+
+                print!("Enter root role (img, group or empty for automatic): ");
+                readln!(&mut role_text);
+
+                print!("Enter labelled layer id patterns (for example valve-*, tank-*): ");
+                readln!(&mut labelled_ids_text);
+
+                let optional =
+                    |text: &str| Some(text.trim().to_string()).filter(|text| !text.is_empty());
+                let role = match role_text.trim() {
+                    "" => Ok(None),
+                    role => DiagramRole::new(role).map(Some),
+                };
+                let labelled_ids = labelled_ids_text
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|pattern| !pattern.is_empty())
+                    .map(String::from)
+                    .collect();
+
+                let accessibility = role.and_then(|role| {
+                    Accessibility::new(
+                        optional(title_text.as_str()),
+                        optional(description_text.as_str()),
+                        role,
+                        labelled_ids,
+                    )
+                });
+
+                match accessibility {
+                    Ok(accessibility) => config.set_accessibility(accessibility),
+                    Err(error) => {
+                        println!("{} Please try again.", error);
+                        continue;
+                    }
+                }
+            }
+            Some(14) => {
+                let mut inline_styles_text = String::new();
+
+                print!("Enter inline style handling (keep, attributes or classes): ");
+                readln!(&mut inline_styles_text);
+
+                match InlineStyles::new(inline_styles_text.trim()) {
+                    Ok(inline_styles) => config.set_inline_styles(inline_styles),
+                    Err(error) => {
+                        println!("{} Please try again.", error);
+                        continue;
+                    }
+                }
+            }
+            Some(15) => {
+                let mut style_format_text = String::new();
+
+                print!("Enter style format (keep, minify or pretty): ");
+                readln!(&mut style_format_text);
+
+                match StyleFormat::new(style_format_text.trim()) {
+                    Ok(style_format) => config.set_style_format(style_format),
+                    Err(error) => {
+                        println!("{} Please try again.", error);
+                        continue;
+                    }
+                }
+            }
+            Some(16) => {
+                config.set_extract_classes(!config.extract_classes());
+
+                println!(
+                    "Class extraction {}.",
+                    if config.extract_classes() {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+            }
+            Some(17) => {
+                config.set_scope_ids(!config.scope_ids());
+
+                println!(
+                    "Per-instance ids {}.",
+                    if config.scope_ids() {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+            }
+            Some(18) => break,
             _ => println!("Invalid option selected. Please try again."),
         }
 
@@ -161,3 +566,33 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/**
+ * Prints the bounding box of the element with the given id in root viewBox coordinates,
+ * for the `bbox <id>` command line query.
+ */
+fn print_bounding_box(config: &Config, id: &str) -> Result<()> {
+    let document = Document::parse(config.read_svg_file()?.as_str())?;
+    let mut found = None;
+
+    document.root().walk(&mut |element: &Element, ancestors| {
+        if found.is_none() && element.attribute("id") == Some(id) {
+            found = Some(BoundingBox::in_root(element, ancestors));
+        }
+    });
+
+    match found {
+        Some(Some(bounds)) => println!(
+            "{}: x={} y={} width={} height={}",
+            id,
+            bounds.min_x(),
+            bounds.min_y(),
+            bounds.width(),
+            bounds.height()
+        ),
+        Some(None) => println!("{}: no geometry", id),
+        None => println!("No element with id {} found.", id),
+    }
+
+    Ok(())
+}
@@ -10,7 +10,19 @@ impl Display for Options {
             "\t3 - Set SVG file path",
             "\t4 - Set style file path",
             "\t5 - Set output file path",
-            "\t6 - Save and exit",
+            "\t6 - Set theme color",
+            "\t7 - Run contrast audit",
+            "\t8 - Run color vision deficiency audit",
+            "\t9 - Set token mapping",
+            "\t10 - Toggle typed script block",
+            "\t11 - Set Svelte target",
+            "\t12 - Set component kind",
+            "\t13 - Set accessibility metadata",
+            "\t14 - Set inline style handling",
+            "\t15 - Set style format",
+            "\t16 - Toggle class extraction",
+            "\t17 - Toggle per-instance ids",
+            "\t18 - Save and exit",
         ];
 
         write!(f, "Options:\n{}", options.join("\n"))
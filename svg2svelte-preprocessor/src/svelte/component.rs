@@ -0,0 +1,1030 @@
+use super::kind::ComponentKind;
+use crate::{
+    config::Config,
+    css::stylesheet::{visit_declarations, Stylesheet},
+    svg::{
+        accessibility::layer_label,
+        document::{decode_entities, Document, Element, Node},
+        geometry::{length, BoundingBox, Transform},
+        passes::extract_images::Asset,
+        var_color::VarColor,
+    },
+};
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::{collections::HashSet, ops::Range};
+
+/**
+ * Pattern for matching a `url(#id)` reference.
+ * Group 1: The referenced id.
+ */
+const URL_REFERENCE_PATTERN: &str = r#"url\(\s*['"]?#([^)'"\s]+)['"]?\s*\)"#;
+
+/**
+ * Pattern for matching a text placeholder such as `{{pt1}}` or `{{pt1|000.0}}`.
+ * Group 1: The prop name.
+ * Group 2: The optional format hint.
+ */
+const PLACEHOLDER_PATTERN: &str = r"\{\{\s*([^{}|\s]+)\s*(?:\|\s*([^{}]*?)\s*)?\}\}";
+
+/**
+ * Attribute holding the original id of a scoped element that the style selects by id.
+ */
+const ID_HOOK_ATTRIBUTE: &str = "data-id";
+
+/**
+ * Name of the per-instance id prefix declared in the script.
+ */
+const UID_BINDING: &str = "uid";
+
+/**
+ * Names a placeholder or state prop cannot take, because they are generated
+ * bindings, types or imports, or reserved words in TypeScript.
+ */
+const RESERVED_NAMES: &[&str] = &[
+    "class",
+    "style",
+    "theme",
+    "children",
+    "className",
+    "dispatch",
+    "createEventDispatcher",
+    "Events",
+    "Snippet",
+    UID_BINDING,
+    "instanceCount",
+    "break",
+    "case",
+    "catch",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "export",
+    "false",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "let",
+    "new",
+    "null",
+    "return",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "with",
+];
+
+/**
+ * A prop exported by a generated component.
+ * The binding is the local variable name, which differs from the
+ * prop name when the name is reserved (`class`).
+ */
+pub struct Prop {
+    name: String,
+    binding: String,
+    type_annotation: String,
+    default: String,
+}
+
+/**
+ * An event dispatched by a generated component, with the TypeScript type of its detail.
+ */
+pub struct Event {
+    name: String,
+    detail_type: String,
+}
+
+/**
+ * An element of the diagram that the props or events of a component refer to.
+ * Bounds are in viewBox units.
+ */
+pub struct Binding {
+    id: String,
+    kind: String,
+    bounds: Option<BoundingBox>,
+    props: Vec<String>,
+    events: Vec<String>,
+}
+
+/**
+ * Kind of the elements bound by text placeholders.
+ */
+const SENSOR_KIND: &str = "sensor";
+
+/**
+ * The target independent representation of a generated component.
+ *
+ * The markup is the root `<svg>` element with generated attributes stored as expression attributes.
+ * Event handlers are stored as `on:<event>` directives and written in the form of the target,
+ * and call `dispatch(name, detail)`, which each target defines for the events of the component.
+ */
+pub struct Component {
+    props: Vec<Prop>,
+    events: Vec<Event>,
+    bindings: Vec<Binding>,
+    module_statements: Vec<String>,
+    statements: Vec<String>,
+    markup: Element,
+    style: String,
+    has_slot: bool,
+}
+
+impl Prop {
+    fn new(name: &str, binding: &str, type_annotation: &str, default: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            binding: binding.to_string(),
+            type_annotation: type_annotation.to_string(),
+            default: default.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn binding(&self) -> &str {
+        self.binding.as_str()
+    }
+
+    pub fn type_annotation(&self) -> &str {
+        self.type_annotation.as_str()
+    }
+
+    pub fn default(&self) -> &str {
+        self.default.as_str()
+    }
+}
+
+impl Event {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn detail_type(&self) -> &str {
+        self.detail_type.as_str()
+    }
+}
+
+impl Binding {
+    pub fn id(&self) -> &str {
+        self.id.as_str()
+    }
+
+    pub fn kind(&self) -> &str {
+        self.kind.as_str()
+    }
+
+    pub fn bounds(&self) -> Option<&BoundingBox> {
+        self.bounds.as_ref()
+    }
+
+    pub fn props(&self) -> &[String] {
+        &self.props
+    }
+
+    pub fn events(&self) -> &[String] {
+        &self.events
+    }
+}
+
+impl Component {
+    /**
+     * Builds the component for a document. When script generation is enabled, the component
+     * exports `class`, `style` and a `theme` object keyed by the mapped variables, applied
+     * on the root element, and accepts child content drawn on top of the diagram.
+     * Text placeholders are always turned into props, and assets referenced by a binding
+     * are imported in the module script.
+     */
+    pub fn new(
+        config: &Config,
+        document: &Document,
+        raw_styling_text: &str,
+        assets: &[Asset],
+    ) -> Result<Self> {
+        let mut component = Self {
+            props: Vec::new(),
+            events: Vec::new(),
+            bindings: Vec::new(),
+            module_statements: Vec::new(),
+            statements: Vec::new(),
+            markup: document.root().clone(),
+            style: raw_styling_text.to_string(),
+            has_slot: false,
+        };
+
+        component.import_assets(assets);
+
+        if config.generate_script() {
+            let theme_vars = theme_vars(config);
+
+            component.bind_root_props(&theme_vars);
+            component.has_slot = true;
+        }
+
+        component.bind_placeholders()?;
+        component.bind_component_kinds(config.component_kinds())?;
+
+        if config.scope_ids() {
+            component.scope_ids(config.component_name().as_str())?;
+        }
+
+        Ok(component)
+    }
+
+    pub fn props(&self) -> &[Prop] {
+        &self.props
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /**
+     * The bound elements, in document order of the first occurrence of each id.
+     */
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /**
+     * Statements run once per module, before any instance is created.
+     */
+    pub fn module_statements(&self) -> &[String] {
+        &self.module_statements
+    }
+
+    /**
+     * Statements run once per instance, after the props are declared.
+     */
+    pub fn statements(&self) -> &[String] {
+        &self.statements
+    }
+
+    pub fn markup(&self) -> &Element {
+        &self.markup
+    }
+
+    pub fn style(&self) -> &str {
+        self.style.as_str()
+    }
+
+    pub fn has_slot(&self) -> bool {
+        self.has_slot
+    }
+
+    /**
+     * Merges the `class` and `style` props into the root attributes and
+     * adds a `style:--var` directive for every theme variable.
+     */
+    fn bind_root_props(&mut self, theme_vars: &[&VarColor]) {
+        let root = &mut self.markup;
+
+        let class = match root.attribute("class") {
+            Some(class) => format!("[{}, className].join(\" \").trim()", js_string(class)),
+            None => "className".to_string(),
+        };
+
+        let style = match root.attribute("style") {
+            Some(style) => format!("[{}, style].filter(Boolean).join(\"; \")", js_string(style)),
+            None => "style".to_string(),
+        };
+
+        root.set_expression_attribute("class", class.as_str());
+        root.set_expression_attribute("style", style.as_str());
+
+        for var in theme_vars {
+            root.set_expression_attribute(
+                format!("style:{}", var.as_str()).as_str(),
+                format!("theme[{}]", js_string(var.as_str())).as_str(),
+            );
+        }
+
+        let theme_type = if theme_vars.is_empty() {
+            "Record<string, never>".to_string()
+        } else {
+            let keys = theme_vars
+                .iter()
+                .map(|var| format!("{}?: string", js_string(var.as_str())))
+                .collect::<Vec<_>>();
+
+            format!("{{ {} }}", keys.join("; "))
+        };
+
+        self.props.extend([
+            Prop::new("class", "className", "string", "\"\""),
+            Prop::new("style", "style", "string | undefined", "undefined"),
+            Prop::new("theme", "theme", theme_type.as_str(), "{}"),
+        ]);
+    }
+
+    /**
+     * Replaces `{{name}}` and `{{name|hint}}` placeholders in the text of `<text>` and `<tspan>`
+     * elements with an expression of an exported prop of that name. A hint such as `000.0`
+     * makes the prop a number shown with as many decimals as the hint, and the hint with
+     * its digits replaced by dashes while the prop is undefined. A prop with a hint in any
+     * of its placeholders is a number in all of them.
+     */
+    fn bind_placeholders(&mut self) -> Result<()> {
+        let placeholder_regex =
+            Regex::new(PLACEHOLDER_PATTERN).expect("Failed to parse placeholder pattern.");
+
+        let mut placeholders: Vec<(String, bool)> = Vec::new();
+        let mut bindings: Vec<Binding> = Vec::new();
+        let mut error = None;
+
+        self.markup.walk_mut(&mut |element, ancestors| {
+            if !matches!(element.name(), "text" | "tspan") {
+                return;
+            }
+
+            let mut children = Vec::new();
+            let mut names: Vec<String> = Vec::new();
+
+            for child in element.children_mut().drain(..) {
+                let text = match child {
+                    Node::Text(text) => text,
+                    child => {
+                        children.push(child);
+                        continue;
+                    }
+                };
+
+                let mut position = 0;
+
+                for captures in placeholder_regex.captures_iter(text.as_str()) {
+                    let placeholder = captures.get(0).expect("Group 0 is the whole match.");
+                    let name = &captures[1];
+                    let hint = captures.get(2).map(|hint| hint.as_str());
+
+                    let expression = match placeholder_expression(name, hint) {
+                        Ok(expression) => expression,
+                        Err(placeholder_error) => {
+                            error.get_or_insert(placeholder_error);
+                            continue;
+                        }
+                    };
+
+                    match placeholders
+                        .iter_mut()
+                        .find(|(existing, _)| existing == name)
+                    {
+                        Some((_, numeric)) => *numeric |= hint.is_some(),
+                        None => placeholders.push((name.to_string(), hint.is_some())),
+                    }
+
+                    if !names.iter().any(|existing| existing == name) {
+                        names.push(name.to_string());
+                    }
+
+                    if placeholder.start() > position {
+                        children.push(Node::Text(text[position..placeholder.start()].to_string()));
+                    }
+
+                    children.push(Node::Expression(expression));
+                    position = placeholder.end();
+                }
+
+                if position < text.len() {
+                    children.push(Node::Text(text[position..].to_string()));
+                }
+            }
+
+            *element.children_mut() = children;
+
+            let text = ancestors
+                .iter()
+                .rev()
+                .find(|ancestor| ancestor.name() == "text");
+
+            let Some(id) = element
+                .attribute("id")
+                .or_else(|| text.and_then(|text| text.attribute("id")))
+                .filter(|_| !names.is_empty())
+                .map(|id| id.to_string())
+            else {
+                return;
+            };
+
+            let coordinate = |name: &str| {
+                length(element.attribute(name))
+                    .or_else(|| text.and_then(|text| length(text.attribute(name))))
+                    .unwrap_or(0.0)
+            };
+            let bounds = Transform::of_ancestors(ancestors)
+                .zip(Transform::of_element(element))
+                .map(|(ancestors, own)| {
+                    let (x, y) = ancestors
+                        .multiply(&own)
+                        .apply((coordinate("x"), coordinate("y")));
+                    BoundingBox::new(x, y, x, y)
+                });
+
+            match bindings.iter_mut().find(|binding| binding.id == id) {
+                Some(binding) => binding.props.extend(
+                    names
+                        .into_iter()
+                        .filter(|name| !binding.props.contains(name))
+                        .collect::<Vec<_>>(),
+                ),
+                None => bindings.push(Binding {
+                    id,
+                    kind: SENSOR_KIND.to_string(),
+                    bounds,
+                    props: names,
+                    events: Vec::new(),
+                }),
+            }
+        });
+
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        self.bindings.extend(bindings);
+
+        for (name, numeric) in placeholders {
+            let type_annotation = if numeric {
+                "number | undefined"
+            } else {
+                "string | number | undefined"
+            };
+
+            self.props.push(Prop::new(
+                name.as_str(),
+                name.as_str(),
+                type_annotation,
+                "undefined",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Gives every element whose id matches a component kind a state prop, shown as `data-state`
+     * and as the `--state-color` of the state, and makes it dispatch a `<kind>click` event
+     * carrying its id when clicked or activated with the keyboard.
+     * The first matching kind applies.
+     */
+    fn bind_component_kinds(&mut self, kinds: &[ComponentKind]) -> Result<()> {
+        let mut bound_ids: Vec<Vec<String>> = kinds.iter().map(|_| Vec::new()).collect();
+        let mut bindings: Vec<Binding> = Vec::new();
+
+        self.markup.walk_mut(&mut |element, ancestors| {
+            let Some(id) = element.attribute("id").map(str::to_string) else {
+                return;
+            };
+
+            let Some(index) = kinds.iter().position(|kind| kind.matches(id.as_str())) else {
+                return;
+            };
+
+            let kind = &kinds[index];
+            let prop = state_prop_name(id.as_str());
+            let dispatch = format!(
+                "dispatch(\"{}click\", {{ id: {} }})",
+                kind.kind(),
+                js_string(id.as_str())
+            );
+
+            element.set_expression_attribute("data-state", prop.as_str());
+
+            if kind.states().values().any(Option::is_some) {
+                element.set_expression_attribute(
+                    "style:--state-color",
+                    format!("{} && {}StateColors[{}]", prop, kind.kind(), prop).as_str(),
+                );
+            }
+
+            element.set_attribute("role", "button");
+            element.set_attribute("tabindex", "0");
+
+            if element.attribute("aria-label").is_none() {
+                element.set_attribute("aria-label", layer_label(id.as_str()).as_str());
+            }
+
+            element.set_expression_attribute("on:click", format!("() => {}", dispatch).as_str());
+            element.set_expression_attribute(
+                "on:keydown",
+                format!(
+                    "(event) => {{ if (event.key === \"Enter\" || event.key === \" \") {{ event.preventDefault(); {}; }} }}",
+                    dispatch
+                )
+                .as_str(),
+            );
+
+            if !bound_ids[index].contains(&id) {
+                bindings.push(Binding {
+                    id: id.clone(),
+                    kind: kind.kind().to_string(),
+                    bounds: BoundingBox::in_root(element, ancestors),
+                    props: vec![prop],
+                    events: vec![format!("{}click", kind.kind())],
+                });
+                bound_ids[index].push(id);
+            }
+        });
+
+        for binding in bindings {
+            if self
+                .bindings
+                .iter()
+                .any(|existing| existing.id == binding.id)
+            {
+                return Err(anyhow!("Element {} is bound twice.", binding.id));
+            }
+
+            self.bindings.push(binding);
+        }
+
+        for (kind, ids) in kinds.iter().zip(bound_ids) {
+            if ids.is_empty() {
+                continue;
+            }
+
+            let states = kind
+                .states()
+                .keys()
+                .map(|state| js_string(state))
+                .collect::<Vec<_>>()
+                .join(" | ");
+
+            for id in ids.iter() {
+                let prop = state_prop_name(id);
+
+                if self.props.iter().any(|existing| existing.name() == prop) {
+                    return Err(anyhow!("Prop {} for {} is generated twice.", prop, id));
+                }
+
+                if RESERVED_NAMES.contains(&prop.as_str()) {
+                    return Err(anyhow!("Prop {} for {} is a reserved name.", prop, id));
+                }
+
+                self.props.push(Prop::new(
+                    prop.as_str(),
+                    prop.as_str(),
+                    format!("{} | undefined", states).as_str(),
+                    "undefined",
+                ));
+            }
+
+            let colors = kind
+                .states()
+                .iter()
+                .filter_map(|(state, var)| {
+                    var.as_ref().map(|var| {
+                        format!(
+                            "{}: {}",
+                            js_string(state),
+                            js_string(format!("var({})", var.as_str()).as_str())
+                        )
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            if !colors.is_empty() {
+                self.module_statements.push(format!(
+                    "const {}StateColors: Record<string, string> = {{ {} }};",
+                    kind.kind(),
+                    colors.join(", ")
+                ));
+            }
+
+            let ids = ids
+                .iter()
+                .map(|id| js_string(id))
+                .collect::<Vec<_>>()
+                .join(" | ");
+
+            self.events.push(Event {
+                name: format!("{}click", kind.kind()),
+                detail_type: format!("{{ id: {} }}", ids),
+            });
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Imports the assets that the markup references by a binding, relative to the component.
+     */
+    fn import_assets(&mut self, assets: &[Asset]) {
+        for asset in assets {
+            if let Some(binding) = asset.binding() {
+                self.module_statements.push(format!(
+                    "import {} from {};",
+                    binding,
+                    js_string(asset.import_path().as_str())
+                ));
+            }
+        }
+    }
+
+    /**
+     * Prefixes every id, and every `url(#id)` and `href="#id"` reference to an id in the markup,
+     * with the component name and a counter incremented for each instance, so gradients and
+     * clip paths of several diagrams on one page do not resolve to each other.
+     *
+     * The style is static, so `#id` selectors in it are rewritten to match a `data-id`
+     * attribute holding the original id, and ids the style references with `url(#id)`
+     * are not scoped.
+     */
+    fn scope_ids(&mut self, component_name: &str) -> Result<()> {
+        let mut ids = HashSet::new();
+
+        self.markup.walk(&mut |element, _| {
+            if let Some(id) = element.attribute("id") {
+                ids.insert(id.to_string());
+            }
+        });
+
+        let url_regex =
+            Regex::new(URL_REFERENCE_PATTERN).expect("Failed to parse url reference pattern.");
+        let mut hooked = HashSet::new();
+
+        if !self.style.trim().is_empty() {
+            let stylesheet = Stylesheet::parse(self.style.as_str())
+                .map_err(|error| anyhow!("Component style: {}", error))?;
+
+            visit_declarations(stylesheet.items(), &mut |declaration| {
+                for captures in url_regex.captures_iter(declaration.value()) {
+                    ids.remove(&captures[1]);
+                }
+            });
+
+            self.style = stylesheet.map_selector_ids(|id| {
+                ids.contains(id).then(|| {
+                    hooked.insert(id.to_string());
+                    format!("[{}={}]", ID_HOOK_ATTRIBUTE, js_string(id))
+                })
+            });
+        }
+
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        self.markup.walk_mut(&mut |element, _| {
+            let hook = element
+                .attribute("id")
+                .filter(|id| hooked.contains(*id))
+                .map(|id| id.to_string());
+
+            let scoped = element
+                .attributes()
+                .iter()
+                .filter(|attribute| !attribute.is_expression())
+                .filter_map(|attribute| {
+                    let value = attribute.value();
+
+                    let references: Vec<_> = match attribute.name() {
+                        "id" => std::iter::once(0..value.len()).collect(),
+                        "aria-labelledby" | "aria-describedby" => id_list_ranges(value),
+                        "href" | "xlink:href" => value
+                            .strip_prefix('#')
+                            .map(|_| 1..value.len())
+                            .into_iter()
+                            .collect(),
+                        _ => url_regex
+                            .captures_iter(value)
+                            .filter_map(|captures| captures.get(1))
+                            .map(|id| id.range())
+                            .collect(),
+                    };
+
+                    let references = references
+                        .into_iter()
+                        .filter(|range| ids.contains(&value[range.clone()]))
+                        .collect::<Vec<_>>();
+
+                    (!references.is_empty()).then(|| {
+                        (
+                            attribute.name().to_string(),
+                            scoped_template(value, &references),
+                        )
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            for (name, template) in scoped {
+                element.set_expression_attribute(name.as_str(), template.as_str());
+            }
+
+            if let Some(id) = hook {
+                element.set_attribute(ID_HOOK_ATTRIBUTE, id.as_str());
+            }
+        });
+
+        self.module_statements
+            .push("let instanceCount = 0;".to_string());
+        self.statements.push(format!(
+            "const {} = `{}-${{++instanceCount}}`;",
+            UID_BINDING, component_name
+        ));
+
+        Ok(())
+    }
+}
+
+/**
+ * Byte ranges of the ids in a whitespace separated id list such as `aria-labelledby`.
+ */
+fn id_list_ranges(value: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+
+    for (index, c) in value
+        .char_indices()
+        .chain(std::iter::once((value.len(), ' ')))
+    {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(index),
+            (Some(id_start), true) => {
+                ranges.push(id_start..index);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    ranges
+}
+
+/**
+ * The state prop of an element, named after its id in camel case (`valve-12` is `valve12State`).
+ */
+fn state_prop_name(id: &str) -> String {
+    let mut name = String::new();
+    let mut capitalize = false;
+
+    for c in id.chars() {
+        if !c.is_ascii_alphanumeric() {
+            capitalize = true;
+            continue;
+        }
+
+        match (name.is_empty(), capitalize) {
+            (true, _) if c.is_ascii_digit() => {
+                name.push('_');
+                name.push(c);
+            }
+            (true, _) => name.push(c.to_ascii_lowercase()),
+            (false, true) => name.push(c.to_ascii_uppercase()),
+            (false, false) => name.push(c),
+        }
+
+        capitalize = false;
+    }
+
+    name + "State"
+}
+
+/**
+ * The expression a placeholder is replaced with.
+ */
+fn placeholder_expression(name: &str, hint: Option<&str>) -> Result<String> {
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+
+    if !is_identifier || RESERVED_NAMES.contains(&name) {
+        return Err(anyhow!("Invalid placeholder name {}.", name));
+    }
+
+    let Some(hint) = hint else {
+        return Ok(format!("{} ?? \"\"", name));
+    };
+
+    let (integer, decimals) = hint.split_once('.').unwrap_or((hint, ""));
+
+    let is_zeros = |part: &str| part.chars().all(|c| c == '0');
+
+    if integer.is_empty() || !is_zeros(integer) || !is_zeros(decimals) {
+        return Err(anyhow!(
+            "Invalid format hint {} for placeholder {}.",
+            hint,
+            name
+        ));
+    }
+
+    let fallback = hint.replace('0', "-");
+
+    Ok(format!(
+        "{} === undefined ? {} : {}.toFixed({})",
+        name,
+        js_string(fallback.as_str()),
+        name,
+        decimals.len()
+    ))
+}
+
+/**
+ * A template literal for a value with the given id ranges prefixed by the instance id.
+ */
+fn scoped_template(value: &str, references: &[Range<usize>]) -> String {
+    let escape = |text: &str| {
+        decode_entities(text)
+            .replace('\\', "\\\\")
+            .replace('`', "\\`")
+            .replace("${", "\\${")
+    };
+
+    let mut template = String::from("`");
+    let mut position = 0;
+
+    for range in references {
+        template.push_str(escape(&value[position..range.start]).as_str());
+        template.push_str(format!("${{{}}}-", UID_BINDING).as_str());
+        template.push_str(escape(&value[range.clone()]).as_str());
+        position = range.end;
+    }
+
+    template.push_str(escape(&value[position..]).as_str());
+    template.push('`');
+    template
+}
+
+/**
+ * Every variable a color or token mapping writes, sorted and without duplicates.
+ */
+fn theme_vars(config: &Config) -> Vec<&VarColor> {
+    let mut vars = config
+        .color_mappings()
+        .iter()
+        .map(|mapping| mapping.var())
+        .chain(config.token_mappings().iter().map(|mapping| mapping.var()))
+        .collect::<Vec<_>>();
+
+    vars.sort_by_key(|var| var.as_str());
+    vars.dedup();
+    vars
+}
+
+pub fn js_string(text: &str) -> String {
+    serde_json::to_string(text).expect("Strings always serialize to JSON.")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{state_prop_name, Component};
+    use crate::{
+        config::Config,
+        svelte::kind::ComponentKind,
+        svg::{
+            document::Document,
+            modifier::{Modifier, Pass},
+            passes::extract_images::ExtractImages,
+        },
+    };
+
+    fn component(input: &str) -> anyhow::Result<Component> {
+        Component::new(
+            &Config::default(),
+            &Document::parse(input).unwrap(),
+            "",
+            &[],
+        )
+    }
+
+    #[test]
+    fn test_placeholders_become_props() {
+        let input = "<svg><text>PT-1: <tspan>{{ pt1|000.0 }} psi</tspan></text><text>{{pt1|0}} {{state}}</text></svg>";
+        let component = component(input).unwrap();
+
+        assert_eq!(
+            component.markup().to_string(),
+            r#"<svg><text>PT-1: <tspan>{pt1 === undefined ? "---.-" : pt1.toFixed(1)} psi</tspan></text><text>{pt1 === undefined ? "-" : pt1.toFixed(0)} {state ?? ""}</text></svg>"#
+        );
+
+        let props = component
+            .props()
+            .iter()
+            .map(|prop| (prop.name(), prop.type_annotation()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            props,
+            [
+                ("pt1", "number | undefined"),
+                ("state", "string | number | undefined")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hinted_placeholder_makes_every_use_numeric() {
+        let input =
+            r#"<svg><text id="pt-1">{{pt1}} / {{pt1|00.0}}</text><text>{{pt2}}</text></svg>"#;
+        let component = component(input).unwrap();
+
+        assert_eq!(
+            component.markup().to_string(),
+            r#"<svg><text id={`${uid}-pt-1`}>{pt1 ?? ""} / {pt1 === undefined ? "--.-" : pt1.toFixed(1)}</text><text>{pt2 ?? ""}</text></svg>"#
+        );
+        assert_eq!(component.props()[0].type_annotation(), "number | undefined");
+
+        let bindings = component
+            .bindings()
+            .iter()
+            .map(|binding| (binding.id(), binding.props()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(bindings, [("pt-1", ["pt1".to_string()].as_slice())]);
+    }
+
+    #[test]
+    fn test_invalid_placeholders_are_err() {
+        assert!(component("<svg><text>{{dispatch}}</text></svg>").is_err());
+        assert!(component("<svg><text>{{className}}</text></svg>").is_err());
+        assert!(component("<svg><text>{{Events}}</text></svg>").is_err());
+        assert!(component("<svg><text>{{class}}</text></svg>").is_err());
+        assert!(component("<svg><text>{{pt-1}}</text></svg>").is_err());
+        assert!(component("<svg><text>{{pt1|0.0.0}}</text></svg>").is_err());
+        assert!(component("<svg><text>{{pt1|abc}}</text></svg>").is_err());
+        assert!(component("<svg><g>{{pt1}}</g></svg>").is_ok());
+    }
+
+    #[test]
+    fn test_component_kinds_bind_state_and_click() {
+        let mut config = Config::default();
+
+        config.set_component_kind(
+            ComponentKind::new(
+                "valve",
+                "valve-*",
+                ComponentKind::parse_states("open=--valve-open, closed").unwrap(),
+            )
+            .unwrap(),
+        );
+
+        let input = r#"<svg><g id="valve-12"><path/></g><g id="pump-1"/></svg>"#;
+        let document = Document::parse(input).unwrap();
+        let component = Component::new(&config, &document, "", &[]).unwrap();
+
+        assert_eq!(
+            component.markup().to_string(),
+            r#"<svg><g id={`${uid}-valve-12`} data-state={valve12State} style:--state-color={valve12State && valveStateColors[valve12State]} role='button' tabindex='0' aria-label='Valve 12' on:click={() => dispatch("valveclick", { id: "valve-12" })} on:keydown={(event) => { if (event.key === "Enter" || event.key === " ") { event.preventDefault(); dispatch("valveclick", { id: "valve-12" }); } }}><path/></g><g id={`${uid}-pump-1`}/></svg>"#
+        );
+        assert_eq!(
+            component.props()[0].type_annotation(),
+            r#""closed" | "open" | undefined"#
+        );
+        assert_eq!(component.events()[0].name(), "valveclick");
+        assert_eq!(component.events()[0].detail_type(), r#"{ id: "valve-12" }"#);
+        assert_eq!(
+            component.module_statements()[0],
+            r#"const valveStateColors: Record<string, string> = { "open": "var(--valve-open)" };"#
+        );
+    }
+
+    #[test]
+    fn test_extracted_images_are_imported() {
+        let input = r#"<svg><image href="data:image/gif;base64,R0lGODlhAQABAAAAACw="/></svg>"#;
+
+        let config = Config::default();
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+
+        ExtractImages::default().run(&mut modifier).unwrap();
+
+        let component =
+            Component::new(&config, modifier.accumulate(), "", modifier.assets()).unwrap();
+        let asset = &modifier.assets()[0];
+        let binding = asset.binding().unwrap();
+
+        assert_eq!(
+            component.module_statements()[0],
+            format!(r#"import {} from "./{}";"#, binding, asset.path())
+        );
+        assert_eq!(
+            component.markup().to_string(),
+            format!("<svg><image href={{{}}}/></svg>", binding)
+        );
+    }
+
+    #[test]
+    fn test_state_prop_name() {
+        assert_eq!(state_prop_name("valve-12"), "valve12State");
+        assert_eq!(
+            state_prop_name("Main_Oxidizer valve"),
+            "mainOxidizerValveState"
+        );
+        assert_eq!(state_prop_name("2-way"), "_2WayState");
+    }
+}
@@ -0,0 +1,151 @@
+use crate::svg::{selector::glob_matches, var_color::VarColor};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Display};
+
+/**
+ * A kind of interactive diagram element, such as a valve or pump, found by its Figma layer id.
+ * Each matching element gets a state prop and dispatches a `<kind>click` event carrying its id.
+ * States may name a color variable the element exposes as `--state-color` while in that state.
+ */
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(try_from = "UncheckedComponentKind")]
+pub struct ComponentKind {
+    kind: String,
+    id_pattern: String,
+    states: BTreeMap<String, Option<VarColor>>,
+}
+
+#[derive(Deserialize)]
+struct UncheckedComponentKind {
+    kind: String,
+    id_pattern: String,
+    states: BTreeMap<String, Option<VarColor>>,
+}
+
+impl ComponentKind {
+    pub fn new(
+        kind: &str,
+        id_pattern: &str,
+        states: BTreeMap<String, Option<VarColor>>,
+    ) -> Result<Self> {
+        let is_lowercase_word = |word: &str| {
+            word.starts_with(|c: char| c.is_ascii_lowercase())
+                && word
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        };
+
+        if !is_lowercase_word(kind) {
+            return Err(anyhow!("Component kinds must be lowercase words."));
+        }
+
+        if id_pattern.is_empty() || id_pattern.contains(char::is_whitespace) {
+            return Err(anyhow!("Invalid id pattern."));
+        }
+
+        if states.is_empty() {
+            return Err(anyhow!("Component kinds need at least one state."));
+        }
+
+        if let Some(state) = states.keys().find(|state| {
+            state.is_empty()
+                || !state
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        }) {
+            return Err(anyhow!("Invalid state name {}.", state));
+        }
+
+        Ok(Self {
+            kind: kind.to_string(),
+            id_pattern: id_pattern.to_string(),
+            states,
+        })
+    }
+
+    pub fn kind(&self) -> &str {
+        self.kind.as_str()
+    }
+
+    pub fn states(&self) -> &BTreeMap<String, Option<VarColor>> {
+        &self.states
+    }
+
+    pub fn matches(&self, id: &str) -> bool {
+        glob_matches(self.id_pattern.as_str(), id)
+    }
+
+    /**
+     * Parses states written as `open=--valve-open, closed=--valve-closed, unknown`.
+     */
+    pub fn parse_states(text: &str) -> Result<BTreeMap<String, Option<VarColor>>> {
+        text.split(',')
+            .map(str::trim)
+            .filter(|state| !state.is_empty())
+            .map(|state| match state.split_once('=') {
+                Some((name, var)) => {
+                    Ok((name.trim().to_string(), Some(VarColor::new(var.trim())?)))
+                }
+                None => Ok((state.to_string(), None)),
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<UncheckedComponentKind> for ComponentKind {
+    type Error = anyhow::Error;
+
+    fn try_from(unchecked: UncheckedComponentKind) -> Result<Self> {
+        Self::new(
+            unchecked.kind.as_str(),
+            unchecked.id_pattern.as_str(),
+            unchecked.states,
+        )
+    }
+}
+
+impl Display for ComponentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let states = self
+            .states
+            .iter()
+            .map(|(state, var)| match var {
+                Some(var) => format!("{}={}", state, var.as_str()),
+                None => state.to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        write!(
+            f,
+            "{} #{} [{}]",
+            self.kind,
+            self.id_pattern,
+            states.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ComponentKind;
+
+    #[test]
+    fn test_component_kind_new() {
+        let states =
+            ComponentKind::parse_states("open=--valve-open, closed=--valve-closed, fault").unwrap();
+        let kind = ComponentKind::new("valve", "valve-*", states.clone()).unwrap();
+
+        assert!(kind.matches("valve-12"));
+        assert!(!kind.matches("pump-1"));
+        assert_eq!(kind.states().len(), 3);
+        assert_eq!(
+            kind.to_string(),
+            "valve #valve-* [closed=--valve-closed, fault, open=--valve-open]"
+        );
+
+        assert!(ComponentKind::new("Valve", "valve-*", states.clone()).is_err());
+        assert!(ComponentKind::new("valve", "", states).is_err());
+        assert!(ComponentKind::parse_states("open=--valve open").is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use super::component::{js_string, Component};
+use serde::Serialize;
+
+/**
+ * Describes the bindable elements of a generated component, so the GUI can be
+ * type checked against the diagram and breaks at compile time when a layer is renamed.
+ */
+#[derive(Serialize)]
+pub struct Manifest {
+    component: String,
+    /**
+     * File name of the SVG the component was generated from.
+     */
+    #[serde(skip)]
+    source: String,
+    elements: Vec<ManifestElement>,
+}
+
+#[derive(Serialize)]
+struct ManifestElement {
+    id: String,
+    kind: String,
+    bounds: Option<ManifestBounds>,
+    props: Vec<String>,
+    events: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ManifestBounds {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Manifest {
+    pub fn new(component_name: &str, source: &str, component: &Component) -> Self {
+        let elements = component
+            .bindings()
+            .iter()
+            .map(|binding| ManifestElement {
+                id: binding.id().to_string(),
+                kind: binding.kind().to_string(),
+                bounds: binding.bounds().map(|bounds| ManifestBounds {
+                    x: bounds.min_x(),
+                    y: bounds.min_y(),
+                    width: bounds.width(),
+                    height: bounds.height(),
+                }),
+                props: binding.props().to_vec(),
+                events: binding.events().to_vec(),
+            })
+            .collect();
+
+        Self {
+            component: component_name.to_string(),
+            source: source.to_string(),
+            elements,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Manifests always serialize to JSON.")
+    }
+
+    /**
+     * TypeScript declarations of the element ids, kinds, props and events of the component.
+     */
+    pub fn to_declaration(&self, component: &Component) -> String {
+        let union = |values: Vec<String>| match values.is_empty() {
+            true => "never".to_string(),
+            false => values.join(" | "),
+        };
+
+        let mut kinds = self
+            .elements
+            .iter()
+            .map(|element| js_string(element.kind.as_str()))
+            .collect::<Vec<_>>();
+
+        kinds.sort();
+        kinds.dedup();
+
+        let ids = self
+            .elements
+            .iter()
+            .map(|element| js_string(element.id.as_str()))
+            .collect();
+
+        let props = component
+            .props()
+            .iter()
+            .map(|prop| format!("\t{}?: {};\n", prop.name(), prop.type_annotation()))
+            .collect::<String>();
+
+        let events = component
+            .events()
+            .iter()
+            .map(|event| format!("\t{}: {};\n", event.name(), event.detail_type()))
+            .collect::<String>();
+
+        let list = |values: &[String]| {
+            let values = values
+                .iter()
+                .map(|value| js_string(value))
+                .collect::<Vec<_>>();
+
+            format!("[{}]", values.join(", "))
+        };
+
+        let elements = self
+            .elements
+            .iter()
+            .map(|element| {
+                format!(
+                    "\t{}: {{ kind: {}; props: {}; events: {} }};\n",
+                    js_string(element.id.as_str()),
+                    js_string(element.kind.as_str()),
+                    list(&element.props),
+                    list(&element.events)
+                )
+            })
+            .collect::<String>();
+
+        format!(
+            "// Generated from {}. Do not edit.\n\nexport type ElementId = {};\n\nexport type ElementKind = {};\n\nexport interface Props {{\n{}}}\n\nexport interface Events {{\n{}}}\n\nexport interface Elements {{\n{}}}\n",
+            self.source,
+            union(ids),
+            union(kinds),
+            props,
+            events,
+            elements
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Manifest;
+    use crate::{
+        config::Config,
+        svelte::{component::Component, kind::ComponentKind},
+        svg::document::Document,
+    };
+
+    fn component() -> Component {
+        let mut config = Config::default();
+
+        config.set_component_kind(
+            ComponentKind::new(
+                "valve",
+                "valve-*",
+                ComponentKind::parse_states("open, closed").unwrap(),
+            )
+            .unwrap(),
+        );
+
+        let input = r#"<svg><g id="valve-1"><rect x="10" y="20" width="4" height="2"/></g><text id="pt-1" x="5" y="6">PT-1: <tspan>{{pt1|0.0}}</tspan></text></svg>"#;
+
+        Component::new(&config, &Document::parse(input).unwrap(), "", &[]).unwrap()
+    }
+
+    #[test]
+    fn test_manifest_json() {
+        let component = component();
+        let manifest = serde_json::from_str::<serde_json::Value>(
+            Manifest::new("diagram", "Diagram.svg", &component)
+                .to_json()
+                .as_str(),
+        )
+        .unwrap();
+
+        let expected = serde_json::json!({
+            "component": "diagram",
+            "elements": [
+                {
+                    "id": "pt-1",
+                    "kind": "sensor",
+                    "bounds": { "x": 5.0, "y": 6.0, "width": 0.0, "height": 0.0 },
+                    "props": ["pt1"],
+                    "events": []
+                },
+                {
+                    "id": "valve-1",
+                    "kind": "valve",
+                    "bounds": { "x": 10.0, "y": 20.0, "width": 4.0, "height": 2.0 },
+                    "props": ["valve1State"],
+                    "events": ["valveclick"]
+                }
+            ]
+        });
+
+        assert_eq!(manifest, expected);
+    }
+
+    #[test]
+    fn test_manifest_declaration() {
+        let component = component();
+        let declaration =
+            Manifest::new("diagram", "Diagram.svg", &component).to_declaration(&component);
+
+        assert!(declaration.starts_with("// Generated from Diagram.svg. Do not edit.\n"));
+        assert!(declaration.contains("export type ElementId = \"pt-1\" | \"valve-1\";\n"));
+        assert!(declaration.contains("export type ElementKind = \"sensor\" | \"valve\";\n"));
+        assert!(declaration.contains("\tpt1?: number | undefined;\n"));
+        assert!(declaration.contains("\tvalveclick: { id: \"valve-1\" };\n"));
+        assert!(declaration.contains(
+            "\t\"valve-1\": { kind: \"valve\"; props: [\"valve1State\"]; events: [\"valveclick\"] };\n"
+        ));
+    }
+}
@@ -1 +1,5 @@
+pub mod component;
+pub mod kind;
+pub mod manifest;
+pub mod target;
 pub mod transpiler;
@@ -0,0 +1,35 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/**
+ * The Svelte version generated components are written for.
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum SvelteTarget {
+    #[default]
+    Svelte4,
+    Svelte5,
+}
+
+impl SvelteTarget {
+    pub fn new(name: &str) -> Result<Self> {
+        match name {
+            "4" | "svelte4" => Ok(SvelteTarget::Svelte4),
+            "5" | "svelte5" => Ok(SvelteTarget::Svelte5),
+            _ => Err(anyhow!("Unsupported Svelte target.")),
+        }
+    }
+}
+
+impl Display for SvelteTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SvelteTarget::Svelte4 => "svelte4",
+            SvelteTarget::Svelte5 => "svelte5",
+        };
+
+        write!(f, "{}", name)
+    }
+}
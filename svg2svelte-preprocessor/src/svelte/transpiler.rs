@@ -1,7 +1,520 @@
+use super::{component::Component, target::SvelteTarget};
+use crate::svg::document::{decode_entities, Element, Node};
+
 pub struct Transpiler;
 
 impl Transpiler {
-    pub fn to_svelte(raw_svg_text: &str, raw_styling_text: &str) -> String {
-        format!("{raw_svg_text}\n<style>\n{raw_styling_text}\n</style>")
+    /**
+     * Writes the root `<svg>` element as Svelte markup followed by the component style block.
+     * Anything outside the root, such as an XML declaration or DOCTYPE, is dropped.
+     * Braces in text and attribute values are escaped so Svelte does not read them
+     * as expressions. `<style>` elements inside the SVG are raw text to Svelte, so their CSS is written as is.
+     */
+    pub fn to_svelte(target: SvelteTarget, component: &Component) -> String {
+        let mut markup = String::new();
+        write_element(
+            target,
+            component.markup(),
+            component.has_slot(),
+            &mut markup,
+        );
+
+        let module_script = match (target, component.module_statements().is_empty()) {
+            (_, true) => String::new(),
+            (SvelteTarget::Svelte4, false) => script(
+                "context=\"module\" lang=\"ts\"",
+                component.module_statements(),
+            ),
+            (SvelteTarget::Svelte5, false) => {
+                script("lang=\"ts\" module", component.module_statements())
+            }
+        };
+
+        let script = match target {
+            _ if component.props().is_empty()
+                && component.events().is_empty()
+                && component.statements().is_empty() =>
+            {
+                String::new()
+            }
+            SvelteTarget::Svelte4 => svelte4_script(component),
+            SvelteTarget::Svelte5 => svelte5_script(component),
+        };
+
+        format!(
+            "{module_script}{script}{markup}\n<style>\n{}\n</style>",
+            component.style()
+        )
+    }
+}
+
+fn script(attributes: &str, lines: &[String]) -> String {
+    let body = lines
+        .iter()
+        .map(|line| match line.is_empty() {
+            true => "\n".to_string(),
+            false => format!("\t{}\n", line),
+        })
+        .collect::<String>();
+
+    format!("<script {attributes}>\n{body}</script>\n\n")
+}
+
+/**
+ * The type alias mapping each event name to its detail type.
+ */
+fn events_type(component: &Component) -> String {
+    let events = component
+        .events()
+        .iter()
+        .map(|event| format!("{}: {}", event.name(), event.detail_type()))
+        .collect::<Vec<_>>();
+
+    format!("type Events = {{ {} }};", events.join("; "))
+}
+
+/**
+ * Props as `export let` declarations, re-exporting bindings whose name is reserved.
+ * Events are dispatched with `createEventDispatcher`.
+ */
+fn svelte4_script(component: &Component) -> String {
+    let mut lines = Vec::new();
+
+    if !component.events().is_empty() {
+        lines.push("import { createEventDispatcher } from \"svelte\";".to_string());
+        lines.push(String::new());
+        lines.push(events_type(component));
+    }
+
+    for prop in component.props() {
+        if prop.name() == prop.binding() {
+            lines.push(format!(
+                "export let {}: {} = {};",
+                prop.name(),
+                prop.type_annotation(),
+                prop.default()
+            ));
+        } else {
+            lines.push(format!(
+                "let {}: {} = {};",
+                prop.binding(),
+                prop.type_annotation(),
+                prop.default()
+            ));
+            lines.push(format!(
+                "export {{ {} as {} }};",
+                prop.binding(),
+                prop.name()
+            ));
+        }
+    }
+
+    if !component.events().is_empty() {
+        lines.push("const dispatch = createEventDispatcher<Events>();".to_string());
+    }
+
+    lines.extend(component.statements().iter().cloned());
+
+    script("lang=\"ts\"", &lines)
+}
+
+/**
+ * Props destructured from `$props()`, with child content passed as the `children` snippet.
+ * Events are callback props named `on<event>`.
+ */
+fn svelte5_script(component: &Component) -> String {
+    let mut lines = Vec::new();
+    let mut bindings = Vec::new();
+    let mut types = Vec::new();
+
+    for prop in component.props() {
+        let binding = if prop.name() == prop.binding() {
+            prop.name().to_string()
+        } else {
+            format!("{}: {}", prop.name(), prop.binding())
+        };
+
+        bindings.push(format!("\t{} = {},", binding, prop.default()));
+        types.push(format!("\t{}?: {};", prop.name(), prop.type_annotation()));
+    }
+
+    for event in component.events() {
+        bindings.push(format!("\ton{},", event.name()));
+        types.push(format!(
+            "\ton{}?: (detail: {}) => void;",
+            event.name(),
+            event.detail_type()
+        ));
+    }
+
+    if component.has_slot() {
+        lines.push("import type { Snippet } from \"svelte\";".to_string());
+        lines.push(String::new());
+        bindings.push("\tchildren,".to_string());
+        types.push("\tchildren?: Snippet;".to_string());
+    }
+
+    if !component.events().is_empty() {
+        lines.push(events_type(component));
+    }
+
+    if !bindings.is_empty() {
+        lines.push("let {".to_string());
+        lines.extend(bindings);
+        lines.push("}: {".to_string());
+        lines.extend(types);
+        lines.push("} = $props();".to_string());
+    }
+
+    if !component.events().is_empty() {
+        let handlers = component
+            .events()
+            .iter()
+            .map(|event| format!("{}: on{}", event.name(), event.name()))
+            .collect::<Vec<_>>();
+
+        lines.push(
+            "function dispatch<E extends keyof Events>(name: E, detail: Events[E]) {".to_string(),
+        );
+        lines.push(format!(
+            "\tconst handlers: {{ [K in keyof Events]?: (detail: Events[K]) => void }} = {{ {} }};",
+            handlers.join(", ")
+        ));
+        lines.push("\thandlers[name]?.(detail);".to_string());
+        lines.push("}".to_string());
+    }
+
+    lines.extend(component.statements().iter().cloned());
+
+    script("lang=\"ts\"", &lines)
+}
+
+/**
+ * Replaces braces with character references, which Svelte decodes back into literal braces.
+ */
+fn escape_braces(text: &str) -> String {
+    text.replace('{', "&#123;").replace('}', "&#125;")
+}
+
+fn write_open_tag(
+    target: SvelteTarget,
+    element: &Element,
+    self_closing: bool,
+    markup: &mut String,
+) {
+    markup.push('<');
+    markup.push_str(element.name());
+
+    for attribute in element.attributes() {
+        if attribute.is_expression() {
+            let name = match (target, attribute.name().strip_prefix("on:")) {
+                (SvelteTarget::Svelte5, Some(event)) => format!("on{}", event),
+                _ => attribute.name().to_string(),
+            };
+
+            markup.push_str(format!(" {}={{{}}}", name, attribute.value()).as_str());
+            continue;
+        }
+
+        let quote = attribute.quote();
+        let value = escape_braces(attribute.quoted_value().as_str());
+
+        markup.push_str(format!(" {}={}{}{}", attribute.name(), quote, value, quote).as_str());
+    }
+
+    markup.push_str(if self_closing { "/>" } else { ">" });
+}
+
+/**
+ * Writes an element and its content. The slot for child content, if any, is drawn last.
+ */
+fn write_element(target: SvelteTarget, element: &Element, has_slot: bool, markup: &mut String) {
+    let self_closing = element.is_self_closing() && !has_slot;
+
+    write_open_tag(target, element, self_closing, markup);
+
+    if self_closing {
+        return;
+    }
+
+    if element.name() == "style" {
+        let css = element
+            .children()
+            .iter()
+            .map(|child| match child {
+                Node::Text(text) => decode_entities(text),
+                Node::CData(data) => data.to_string(),
+                _ => String::new(),
+            })
+            .collect::<String>();
+
+        markup.push_str(escape_style_end(css.as_str()).as_str());
+    } else {
+        for child in element.children() {
+            write_markup(target, child, markup);
+        }
+    }
+
+    if has_slot {
+        markup.push_str(match target {
+            SvelteTarget::Svelte4 => "<slot />",
+            SvelteTarget::Svelte5 => "{@render children?.()}",
+        });
+    }
+
+    markup.push_str(format!("</{}>", element.name()).as_str());
+}
+
+/**
+ * Escapes `</style` in CSS written inside a `<style>` element, which would otherwise end it.
+ * `<\/style` reads the same in CSS.
+ */
+fn escape_style_end(css: &str) -> String {
+    let lowercase = css.to_ascii_lowercase();
+    let mut escaped = String::with_capacity(css.len());
+    let mut rest = 0;
+
+    for (index, _) in lowercase.match_indices("</style") {
+        escaped.push_str(&css[rest..index + 1]);
+        escaped.push('\\');
+        rest = index + 1;
+    }
+
+    escaped.push_str(&css[rest..]);
+    escaped
+}
+
+fn write_markup(target: SvelteTarget, node: &Node, markup: &mut String) {
+    match node {
+        Node::Element(element) => write_element(target, element, false, markup),
+        Node::Text(text) => markup.push_str(escape_braces(text).as_str()),
+        Node::Expression(expression) => markup.push_str(format!("{{{}}}", expression).as_str()),
+        Node::CData(data) => {
+            let escaped = data
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+
+            markup.push_str(escape_braces(escaped.as_str()).as_str());
+        }
+        node => markup.push_str(node.to_string().as_str()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Transpiler;
+    use crate::{
+        config::Config,
+        svelte::{component::Component, kind::ComponentKind, target::SvelteTarget},
+        svg::{
+            color_mapping::ColorMapping,
+            css_color::CssColor,
+            document::{Document, Node},
+            var_color::VarColor,
+        },
+    };
+
+    fn transpile(config: &Config, document: &Document, raw_styling_text: &str) -> String {
+        let component = Component::new(config, document, raw_styling_text, &[]).unwrap();
+
+        Transpiler::to_svelte(config.svelte_target(), &component)
+    }
+
+    fn to_svelte(input: &str) -> String {
+        transpile(
+            &Config::default(),
+            &Document::parse(input).unwrap(),
+            ".a { fill: red; }",
+        )
+    }
+
+    #[test]
+    fn test_text_and_attribute_braces_are_escaped() {
+        let input = r#"<svg data-config='{"a": 1}'><text>{pt1} } {</text><![CDATA[<{x}>]]></svg>"#;
+        let expected = r#"<svg data-config='&#123;"a": 1&#125;'><text>&#123;pt1&#125; &#125; &#123;</text>&lt;&#123;x&#125;&gt;</svg>"#;
+
+        assert_eq!(
+            to_svelte(input),
+            format!("{}\n<style>\n.a {{ fill: red; }}\n</style>", expected)
+        );
+    }
+
+    #[test]
+    fn test_attribute_quotes_are_escaped() {
+        let mut document = Document::parse(r#"<svg><text font-family='Inter'/></svg>"#).unwrap();
+        let Node::Element(text) = &mut document.root_mut().children_mut()[0] else {
+            unreachable!()
+        };
+
+        text.set_attribute("font-family", "'Inter', sans-serif");
+        text.set_attribute("aria-label", r#"Valve "A" {open}"#);
+
+        let result = transpile(&Config::default(), &document, "");
+
+        assert!(result.starts_with(
+            r#"<svg><text font-family='&apos;Inter&apos;, sans-serif' aria-label='Valve "A" &#123;open&#125;'/></svg>"#
+        ));
+    }
+
+    #[test]
+    fn test_embedded_style_is_written_verbatim() {
+        let input = r#"<svg><style type="text/css">.cls-1{fill:"x" &gt; 1}</style><style><![CDATA[.b{content:"</STYLE>"}]]></style><style/></svg>"#;
+        let result = to_svelte(input);
+
+        assert!(result.starts_with(
+            r#"<svg><style type="text/css">.cls-1{fill:"x" > 1}</style><style>.b{content:"<\/STYLE>"}</style><style/></svg>"#
+        ));
+    }
+
+    #[test]
+    fn test_component_style_is_kept_verbatim() {
+        let result = to_svelte("<svg><!-- {c} --></svg>");
+
+        assert_eq!(
+            result,
+            "<svg><!-- {c} --></svg>\n<style>\n.a { fill: red; }\n</style>"
+        );
+    }
+
+    #[test]
+    fn test_only_root_is_emitted() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+<!-- Generator: Adobe Illustrator -->
+<svg><path/></svg>
+"#;
+
+        assert!(to_svelte(input).starts_with("<svg><path/></svg>\n<style>"));
+    }
+
+    fn themed_config(target: SvelteTarget) -> Config {
+        let mut config = Config::default();
+
+        config.set_generate_script(true);
+        config.set_svelte_target(target);
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("#000000").unwrap(),
+            VarColor::new("--ink").unwrap(),
+        ));
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("#0000ff").unwrap(),
+            VarColor::new("--pipe").unwrap(),
+        ));
+
+        config
+    }
+
+    #[test]
+    fn test_svelte4_script_exports_theme_props() {
+        let config = themed_config(SvelteTarget::Svelte4);
+        let document = Document::parse("<svg class='diagram'><path/></svg>").unwrap();
+        let result = transpile(&config, &document, "");
+
+        assert!(result.starts_with("<script lang=\"ts\">\n"));
+        assert!(
+            result.contains("\tlet className: string = \"\";\n\texport { className as class };\n")
+        );
+        assert!(result
+            .contains("\texport let theme: { \"--ink\"?: string; \"--pipe\"?: string } = {};\n"));
+        assert!(result.contains(
+            r#"<svg class={["diagram", className].join(" ").trim()} style={style} style:--ink={theme["--ink"]} style:--pipe={theme["--pipe"]}><path/><slot /></svg>"#
+        ));
+    }
+
+    #[test]
+    fn test_svelte5_script_uses_props_rune() {
+        let config = themed_config(SvelteTarget::Svelte5);
+        let mut document = Document::parse("<svg/>").unwrap();
+
+        document
+            .root_mut()
+            .set_expression_attribute("on:click", "() => {}");
+
+        let result = transpile(&config, &document, "");
+
+        assert!(result.contains("\timport type { Snippet } from \"svelte\";\n"));
+        assert!(result.contains("\t\tclass: className = \"\",\n"));
+        assert!(result.contains("\t\tchildren,\n\t}: {\n"));
+        assert!(result.contains("\t\tchildren?: Snippet;\n\t} = $props();\n"));
+        assert!(!result.contains("export"));
+        assert!(result.contains("onclick={() => {}}"));
+        assert!(result.contains(">{@render children?.()}</svg>"));
+    }
+
+    #[test]
+    fn test_ids_are_scoped_per_instance() {
+        let input = r##"<svg><g clip-path="url(#clip0_12_34)" style="fill: url('#paint0')"><use href="#clip0_12_34" xlink:href="#missing"/></g><defs><clipPath id="clip0_12_34"/><linearGradient id="paint0"/></defs></svg>"##;
+        let result = to_svelte(input);
+
+        assert!(result.starts_with(
+            "<script context=\"module\" lang=\"ts\">\n\tlet instanceCount = 0;\n</script>\n\n<script lang=\"ts\">\n\tconst uid = `diagram-${++instanceCount}`;\n</script>\n\n"
+        ));
+        assert!(result.contains(
+            r##"<g clip-path={`url(#${uid}-clip0_12_34)`} style={`fill: url('#${uid}-paint0')`}><use href={`#${uid}-clip0_12_34`} xlink:href="#missing"/></g>"##
+        ));
+        assert!(result.contains(r##"<clipPath id={`${uid}-clip0_12_34`}/>"##));
+
+        let labelled =
+            to_svelte(r#"<svg aria-labelledby=" title  other"><title id="title">A</title></svg>"#);
+
+        assert!(labelled.contains(r#"<svg aria-labelledby={` ${uid}-title  other`}>"#));
+    }
+
+    #[test]
+    fn test_style_id_selectors_match_scoped_ids() {
+        let input = r##"<svg><g id="tank" fill="url(#fade)"><path id="pipe"/></g><linearGradient id="fade"/></svg>"##;
+        let style = "#tank > path, #pipe:hover { fill: red; }\n#fade { stroke: url(#fade); }";
+        let result = transpile(&Config::default(), &Document::parse(input).unwrap(), style);
+
+        assert!(result.contains(
+            r##"<g id={`${uid}-tank`} fill="url(#fade)" data-id='tank'><path id={`${uid}-pipe`} data-id='pipe'/></g><linearGradient id="fade"/>"##
+        ));
+        assert!(result.ends_with(
+            "<style>\n[data-id=\"tank\"] > path, [data-id=\"pipe\"]:hover { fill: red; }\n#fade { stroke: url(#fade); }\n</style>"
+        ));
+    }
+
+    #[test]
+    fn test_scoping_ids_can_be_disabled() {
+        let mut config = Config::default();
+        config.set_scope_ids(false);
+
+        let input = r##"<svg><g clip-path="url(#clip)"/><clipPath id="clip"/></svg>"##;
+        let result = transpile(&config, &Document::parse(input).unwrap(), "#clip { }");
+
+        assert_eq!(
+            result,
+            "<svg><g clip-path=\"url(#clip)\"/><clipPath id=\"clip\"/></svg>\n<style>\n#clip { }\n</style>"
+        );
+    }
+
+    #[test]
+    fn test_click_events_per_target() {
+        let mut config = Config::default();
+
+        config.set_component_kind(
+            ComponentKind::new(
+                "valve",
+                "valve-*",
+                ComponentKind::parse_states("open, closed").unwrap(),
+            )
+            .unwrap(),
+        );
+
+        let document = Document::parse(r#"<svg><g id="valve-1"/></svg>"#).unwrap();
+        let svelte4 = transpile(&config, &document, "");
+
+        config.set_svelte_target(SvelteTarget::Svelte5);
+
+        let svelte5 = transpile(&config, &document, "");
+
+        assert!(svelte4.contains("\ttype Events = { valveclick: { id: \"valve-1\" } };\n"));
+        assert!(svelte4.contains("\tconst dispatch = createEventDispatcher<Events>();\n"));
+        assert!(svelte4.contains(" on:click={() => dispatch("));
+        assert!(svelte5.contains("\t\tonvalveclick?: (detail: { id: \"valve-1\" }) => void;\n"));
+        assert!(svelte5.contains("\t\thandlers[name]?.(detail);\n"));
+        assert!(svelte5.contains(" onclick={() => dispatch("));
+        assert!(svelte5.contains(" onkeydown={(event) => "));
     }
 }
@@ -0,0 +1,229 @@
+use super::selector::glob_matches;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/**
+ * Role of the root `<svg>`. An image is announced as one graphic with the root title,
+ * while a group lets screen readers reach labelled and interactive elements inside it.
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagramRole {
+    Img,
+    Group,
+}
+
+/**
+ * Accessibility metadata added to generated diagrams.
+ * Elements whose Figma layer id matches a labelled id pattern get an `aria-label`
+ * derived from the layer name, so `oxidizer-tank` is announced as "Oxidizer tank".
+ */
+#[derive(Serialize, Deserialize, Default, PartialEq, Debug)]
+#[serde(try_from = "UncheckedAccessibility")]
+pub struct Accessibility {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    role: Option<DiagramRole>,
+    #[serde(default)]
+    labelled_ids: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct UncheckedAccessibility {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    role: Option<DiagramRole>,
+    #[serde(default)]
+    labelled_ids: Vec<String>,
+}
+
+impl DiagramRole {
+    pub fn new(role: &str) -> Result<Self> {
+        match role {
+            "img" => Ok(DiagramRole::Img),
+            "group" => Ok(DiagramRole::Group),
+            _ => Err(anyhow!("Unsupported role.")),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiagramRole::Img => "img",
+            DiagramRole::Group => "group",
+        }
+    }
+}
+
+impl Accessibility {
+    pub fn new(
+        title: Option<String>,
+        description: Option<String>,
+        role: Option<DiagramRole>,
+        labelled_ids: Vec<String>,
+    ) -> Result<Self> {
+        if let Some(pattern) = labelled_ids
+            .iter()
+            .find(|pattern| pattern.is_empty() || pattern.contains(char::is_whitespace))
+        {
+            return Err(anyhow!("Invalid id pattern {}.", pattern));
+        }
+
+        Ok(Self {
+            title,
+            description,
+            role,
+            labelled_ids,
+        })
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /**
+     * The configured role, or `group` when the diagram has labelled or interactive elements
+     * that an image role would hide from screen readers.
+     */
+    pub fn role(&self, has_interactive_elements: bool) -> DiagramRole {
+        self.role.unwrap_or(
+            match has_interactive_elements || !self.labelled_ids.is_empty() {
+                true => DiagramRole::Group,
+                false => DiagramRole::Img,
+            },
+        )
+    }
+
+    pub fn is_labelled(&self, id: &str) -> bool {
+        self.labelled_ids
+            .iter()
+            .any(|pattern| glob_matches(pattern, id))
+    }
+}
+
+impl TryFrom<UncheckedAccessibility> for Accessibility {
+    type Error = anyhow::Error;
+
+    fn try_from(unchecked: UncheckedAccessibility) -> Result<Self> {
+        Self::new(
+            unchecked.title,
+            unchecked.description,
+            unchecked.role,
+            unchecked.labelled_ids,
+        )
+    }
+}
+
+impl Display for Accessibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let labelled_ids = if self.labelled_ids.is_empty() {
+            "none".to_string()
+        } else {
+            self.labelled_ids.join(", ")
+        };
+
+        write!(
+            f,
+            "title \"{}\", role {}, labelled ids {}",
+            self.title.as_deref().unwrap_or(""),
+            self.role.map_or("auto", |role| role.as_str()),
+            labelled_ids
+        )
+    }
+}
+
+/**
+ * A readable label from a Figma layer name, splitting on `-`, `_` and camel case,
+ * for example `oxidizerTank-2` is "Oxidizer tank 2".
+ */
+pub fn layer_label(id: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut previous: Option<char> = None;
+
+    for c in id.chars() {
+        let starts_word = match previous {
+            None => true,
+            Some(previous) => {
+                matches!(previous, '-' | '_' | ' ')
+                    || (previous.is_lowercase() && c.is_uppercase())
+                    || (previous.is_alphabetic() && c.is_ascii_digit())
+            }
+        };
+
+        previous = Some(c);
+
+        if matches!(c, '-' | '_' | ' ') {
+            continue;
+        }
+
+        match words.last_mut() {
+            Some(word) if !starts_word => word.push(c),
+            _ => words.push(c.to_string()),
+        }
+    }
+
+    let label = words
+        .iter()
+        .map(
+            |word| match word.chars().all(char::is_uppercase) && word.len() > 1 {
+                true => word.to_string(),
+                false => word.to_lowercase(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut chars = label.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => label,
+    }
+}
+
+/**
+ * Escapes text for use as character data in generated markup.
+ */
+pub fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{layer_label, Accessibility, DiagramRole};
+
+    #[test]
+    fn test_layer_label() {
+        assert_eq!(layer_label("oxidizer-tank"), "Oxidizer tank");
+        assert_eq!(layer_label("oxidizerTank-2"), "Oxidizer tank 2");
+        assert_eq!(layer_label("valve12"), "Valve 12");
+        assert_eq!(layer_label("LOX_vent"), "LOX vent");
+        assert_eq!(layer_label(""), "");
+    }
+
+    #[test]
+    fn test_role_defaults_to_group_for_labelled_diagrams() {
+        let plain = Accessibility::default();
+        let labelled = Accessibility::new(None, None, None, vec!["valve-*".to_string()]).unwrap();
+        let image = Accessibility::new(None, None, Some(DiagramRole::Img), Vec::new()).unwrap();
+
+        assert_eq!(plain.role(false), DiagramRole::Img);
+        assert_eq!(plain.role(true), DiagramRole::Group);
+        assert_eq!(labelled.role(false), DiagramRole::Group);
+        assert_eq!(image.role(true), DiagramRole::Img);
+        assert!(labelled.is_labelled("valve-12"));
+        assert!(Accessibility::new(None, None, None, vec!["a b".to_string()]).is_err());
+    }
+}
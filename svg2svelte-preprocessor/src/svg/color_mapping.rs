@@ -0,0 +1,534 @@
+use super::{css_color::CssColor, document::Element, selector::Selector, var_color::VarColor};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
+
+/**
+ * Attributes (and `style` declarations) that carry a paint or color value.
+ */
+pub const PAINT_ATTRIBUTES: &[&str] = &[
+    "fill",
+    "stroke",
+    "stop-color",
+    "flood-color",
+    "lighting-color",
+    "color",
+];
+
+/**
+ * The paint properties a color mapping can be limited to.
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum PaintProperty {
+    Fill,
+    Stroke,
+    StopColor,
+}
+
+/**
+ * Maps a color to a CSS variable, optionally only for one paint property
+ * and only on elements matching a selector.
+ */
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct ColorMapping {
+    color: CssColor,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    property: Option<PaintProperty>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selector: Option<Selector>,
+    var: VarColor,
+}
+
+/**
+ * The color mapping table.
+ * Older configuration files stored it as an object from color to variable,
+ * which is still accepted when loading.
+ */
+#[derive(Serialize, Default)]
+pub struct ColorMappings(Vec<ColorMapping>);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredColorMappings {
+    Rules(Vec<ColorMapping>),
+    Legacy(BTreeMap<CssColor, VarColor>),
+}
+
+impl PaintProperty {
+    pub fn new(name: &str) -> Result<Self> {
+        match name {
+            "fill" => Ok(PaintProperty::Fill),
+            "stroke" => Ok(PaintProperty::Stroke),
+            "stop-color" => Ok(PaintProperty::StopColor),
+            _ => Err(anyhow!("Unsupported paint property.")),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaintProperty::Fill => "fill",
+            PaintProperty::Stroke => "stroke",
+            PaintProperty::StopColor => "stop-color",
+        }
+    }
+}
+
+impl ColorMapping {
+    pub fn new(color: CssColor, var: VarColor) -> Self {
+        Self {
+            color,
+            property: None,
+            selector: None,
+            var,
+        }
+    }
+
+    pub fn with_property(mut self, property: PaintProperty) -> Self {
+        self.property = Some(property);
+        self
+    }
+
+    pub fn with_selector(mut self, selector: Selector) -> Self {
+        self.selector = Some(selector);
+        self
+    }
+
+    pub fn var(&self) -> &VarColor {
+        &self.var
+    }
+
+    /**
+     * Whether the mapping applies to a paint attribute of an element.
+     */
+    pub fn applies_to(
+        &self,
+        attribute_name: &str,
+        element: &Element,
+        ancestors: &[&Element],
+    ) -> bool {
+        self.property
+            .is_none_or(|property| property.as_str() == attribute_name)
+            && self
+                .selector
+                .as_ref()
+                .is_none_or(|selector| selector.matches(element, ancestors))
+    }
+
+    /**
+     * Whether the mapping applies to a paint declaration in a stylesheet, where there is no element
+     * to match a selector against.
+     */
+    pub fn applies_to_stylesheet(&self, property: &str) -> bool {
+        self.selector.is_none()
+            && self
+                .property
+                .is_none_or(|mapping_property| mapping_property.as_str() == property)
+    }
+
+    /**
+     * Selector specificity first, then whether a property is given.
+     */
+    pub fn specificity(&self) -> ((usize, usize, usize), bool) {
+        (
+            self.selector
+                .as_ref()
+                .map_or((0, 0, 0), |selector| selector.specificity()),
+            self.property.is_some(),
+        )
+    }
+
+    fn has_same_key(&self, other: &ColorMapping) -> bool {
+        self.color == other.color
+            && self.property == other.property
+            && self.selector == other.selector
+    }
+}
+
+impl Display for ColorMapping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.color.as_str())?;
+
+        if let Some(property) = self.property {
+            write!(f, " [{}]", property.as_str())?;
+        }
+
+        if let Some(selector) = self.selector.as_ref() {
+            write!(f, " {{{}}}", selector.as_str())?;
+        }
+
+        write!(f, " -> {}", self.var.as_str())
+    }
+}
+
+impl ColorMappings {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ColorMapping> {
+        self.0.iter()
+    }
+
+    /**
+     * Adds a mapping, replacing any mapping with the same color, property and selector.
+     */
+    pub fn insert(&mut self, mapping: ColorMapping) {
+        self.0.retain(|existing| !existing.has_same_key(&mapping));
+        self.0.push(mapping);
+    }
+
+    /**
+     * Mappings that apply to a paint attribute of an element, most specific first.
+     */
+    pub fn applicable(
+        &self,
+        attribute_name: &str,
+        element: &Element,
+        ancestors: &[&Element],
+    ) -> Vec<&ColorMapping> {
+        let mut applicable = self
+            .0
+            .iter()
+            .filter(|mapping| mapping.applies_to(attribute_name, element, ancestors))
+            .collect::<Vec<_>>();
+
+        applicable.sort_by_key(|mapping| std::cmp::Reverse(mapping.specificity()));
+        applicable
+    }
+
+    /**
+     * The variable a color becomes in a paint attribute of an element.
+     */
+    pub fn lookup(
+        &self,
+        color: &CssColor,
+        attribute_name: &str,
+        element: &Element,
+        ancestors: &[&Element],
+    ) -> Option<&VarColor> {
+        self.applicable(attribute_name, element, ancestors)
+            .into_iter()
+            .find(|mapping| mapping.color == *color)
+            .map(|mapping| &mapping.var)
+    }
+
+    /**
+     * The mappings indexed by color, for replacing the colors of paint values.
+     */
+    pub fn index(&self) -> ColorIndex<'_> {
+        let mut index: HashMap<&str, Vec<&ColorMapping>> = HashMap::new();
+
+        for mapping in self.0.iter() {
+            index
+                .entry(mapping.color.as_str())
+                .or_default()
+                .push(mapping);
+        }
+
+        ColorIndex(index)
+    }
+
+    /**
+     * The first color that is mapped to a variable.
+     */
+    pub fn source_color(&self, var: &VarColor) -> Option<&CssColor> {
+        self.0
+            .iter()
+            .find(|mapping| mapping.var == *var)
+            .map(|mapping| &mapping.color)
+    }
+
+    /**
+     * Every mapped variable, sorted and without duplicates.
+     */
+    pub fn var_colors(&self) -> Vec<&VarColor> {
+        let mut var_colors = self
+            .0
+            .iter()
+            .map(|mapping| &mapping.var)
+            .collect::<Vec<_>>();
+
+        var_colors.sort_by_key(|var_color| var_color.as_str());
+        var_colors.dedup();
+        var_colors
+    }
+}
+
+/**
+ * Color mappings indexed by color, in table order for each color.
+ */
+pub struct ColorIndex<'a>(HashMap<&'a str, Vec<&'a ColorMapping>>);
+
+impl ColorIndex<'_> {
+    /**
+     * Replaces each color of a paint value with the variable of the most specific mapping
+     * that applies, or the first listed one when several are equally specific. The value is
+     * scanned once, and only whole hex colors and color names are looked up, so `#000` does
+     * not match inside `#0000ff` and the variables put in are never matched again.
+     * Returns `None` when nothing was replaced.
+     */
+    pub fn replace(&self, value: &str, applies: impl Fn(&ColorMapping) -> bool) -> Option<String> {
+        if self.0.is_empty() {
+            return None;
+        }
+
+        let mut replaced = String::new();
+        let mut end = 0;
+
+        for (start, token_end) in color_tokens(value) {
+            let token = &value[start..token_end];
+            let mappings = match token.bytes().any(|byte| byte.is_ascii_uppercase()) {
+                true => self.0.get(token.to_ascii_lowercase().as_str()),
+                false => self.0.get(token),
+            };
+
+            let Some(mapping) = mappings.and_then(|mappings| {
+                mappings
+                    .iter()
+                    .filter(|mapping| applies(mapping))
+                    .min_by_key(|mapping| std::cmp::Reverse(mapping.specificity()))
+            }) else {
+                continue;
+            };
+
+            replaced.push_str(&value[end..start]);
+            replaced.push_str("var(");
+            replaced.push_str(mapping.var.as_str());
+            replaced.push(')');
+            end = token_end;
+        }
+
+        if end == 0 {
+            return None;
+        }
+
+        replaced.push_str(&value[end..]);
+        Some(replaced)
+    }
+}
+
+/**
+ * The byte ranges of the tokens of a paint value that may be colors: hex colors and
+ * identifiers. Function names, custom properties, numbers with their units, strings and
+ * the contents of `url()` are skipped.
+ */
+fn color_tokens(value: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let bytes = value.as_bytes();
+    let mut index = 0;
+
+    let skip_while = move |index: &mut usize, predicate: fn(u8) -> bool| {
+        while *index < bytes.len() && predicate(bytes[*index]) {
+            *index += 1;
+        }
+    };
+    fn is_name(byte: u8) -> bool {
+        byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_')
+    }
+
+    std::iter::from_fn(move || {
+        while index < bytes.len() {
+            let start = index;
+
+            match bytes[start] {
+                b'#' => {
+                    index += 1;
+                    skip_while(&mut index, is_name);
+                    return Some((start, index));
+                }
+                byte if byte.is_ascii_alphabetic() || matches!(byte, b'-' | b'_') => {
+                    skip_while(&mut index, is_name);
+
+                    match bytes.get(index) {
+                        Some(b'(') if value[start..index].eq_ignore_ascii_case("url") => {
+                            skip_while(&mut index, |byte| byte != b')');
+                        }
+                        Some(b'(') => {}
+                        _ if byte == b'-' => {}
+                        _ => return Some((start, index)),
+                    }
+                }
+                byte if byte.is_ascii_digit() || byte == b'.' => {
+                    skip_while(&mut index, |byte| is_name(byte) || byte == b'.');
+                }
+                quote @ (b'"' | b'\'') => {
+                    index += 1;
+
+                    while index < bytes.len() && bytes[index] != quote {
+                        index += 1;
+                    }
+
+                    index += 1;
+                }
+                _ => index += 1,
+            }
+        }
+
+        None
+    })
+}
+
+impl<'de> Deserialize<'de> for ColorMappings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match StoredColorMappings::deserialize(deserializer)? {
+            StoredColorMappings::Rules(mappings) => Self(mappings),
+            StoredColorMappings::Legacy(mappings) => Self(
+                mappings
+                    .into_iter()
+                    .map(|(color, var)| ColorMapping::new(color, var))
+                    .collect(),
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ColorMapping, ColorMappings, PaintProperty};
+    use crate::svg::{
+        css_color::CssColor, document::Document, selector::Selector, var_color::VarColor,
+    };
+
+    fn mapping(color: &str, var: &str) -> ColorMapping {
+        ColorMapping::new(CssColor::new(color).unwrap(), VarColor::new(var).unwrap())
+    }
+
+    #[test]
+    fn test_most_specific_mapping_wins() {
+        let mut mappings = ColorMappings::default();
+        let black = CssColor::new("#000000").unwrap();
+        let document =
+            Document::parse(r#"<svg><g id="oxidizer-tank"><path/><text/></g><path/><text/></svg>"#)
+                .unwrap();
+
+        mappings.insert(mapping("#000000", "--ink"));
+        mappings.insert(mapping("#000000", "--pipe").with_property(PaintProperty::Stroke));
+        mappings.insert(
+            mapping("#000000", "--label")
+                .with_property(PaintProperty::Fill)
+                .with_selector(Selector::new("text").unwrap()),
+        );
+        mappings.insert(mapping("#000000", "--text").with_selector(Selector::new("text").unwrap()));
+        mappings.insert(
+            mapping("#000000", "--ox-accent")
+                .with_selector(Selector::new("#oxidizer-tank *").unwrap()),
+        );
+
+        let mut found = Vec::new();
+
+        document.root().walk(&mut |element, ancestors| {
+            if element.name() == "svg" || element.name() == "g" {
+                return;
+            }
+
+            for attribute in ["fill", "stroke"] {
+                found.push(
+                    mappings
+                        .lookup(&black, attribute, element, ancestors)
+                        .map(|var| var.as_str()),
+                );
+            }
+        });
+
+        assert_eq!(
+            found,
+            vec![
+                Some("--ox-accent"),
+                Some("--ox-accent"),
+                Some("--ox-accent"),
+                Some("--ox-accent"),
+                Some("--ink"),
+                Some("--pipe"),
+                Some("--label"),
+                Some("--text"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_insert_replaces_same_key() {
+        let mut mappings = ColorMappings::default();
+
+        mappings.insert(mapping("red", "--a"));
+        mappings.insert(mapping("red", "--b").with_property(PaintProperty::Fill));
+        mappings.insert(mapping("red", "--c"));
+
+        assert_eq!(mappings.iter().count(), 2);
+        assert_eq!(mappings.var_colors().len(), 2);
+        assert_eq!(
+            mappings.source_color(&VarColor::new("--c").unwrap()),
+            Some(&CssColor::new("red").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_index_replaces_whole_tokens_once() {
+        let mut mappings = ColorMappings::default();
+
+        mappings.insert(mapping("red", "--red"));
+        mappings.insert(mapping("#000", "--black"));
+        mappings.insert(mapping("#0000ff", "--blue"));
+        mappings.insert(mapping("#0000ff", "--line").with_property(PaintProperty::Stroke));
+
+        let index = mappings.index();
+        let replace = |value: &str| index.replace(value, |mapping| mapping.property.is_none());
+
+        assert_eq!(replace("RED").as_deref(), Some("var(--red)"));
+        assert_eq!(replace("#0000FF").as_deref(), Some("var(--blue)"));
+        assert_eq!(
+            replace("#000 url(#red) red").as_deref(),
+            Some("var(--black) url(#red) var(--red)")
+        );
+        assert_eq!(
+            replace("var(--red, red)").as_deref(),
+            Some("var(--red, var(--red))")
+        );
+        assert_eq!(replace("darkred #0000f0 #00000"), None);
+        assert_eq!(replace("'red' 1red --red"), None);
+        assert_eq!(
+            index
+                .replace("#0000ff", |mapping| mapping.applies_to_stylesheet("stroke"))
+                .as_deref(),
+            Some("var(--line)")
+        );
+        assert_eq!(
+            ColorMappings::default().index().replace("red", |_| true),
+            None
+        );
+    }
+
+    #[test]
+    fn test_deserialize_legacy_and_rules() {
+        let legacy = r##"{"#ff0000": "--alarm", "#cc0000": "--alarm", "#00ff00": "--ok"}"##;
+        let rules = r##"[{"color": "#000000", "property": "stroke", "selector": "path", "var": "--pipe"}]"##;
+
+        let legacy = serde_json::from_str::<ColorMappings>(legacy).unwrap();
+        let rules = serde_json::from_str::<ColorMappings>(rules).unwrap();
+
+        assert_eq!(
+            legacy.iter().collect::<Vec<_>>(),
+            vec![
+                &mapping("#00ff00", "--ok"),
+                &mapping("#cc0000", "--alarm"),
+                &mapping("#ff0000", "--alarm"),
+            ]
+        );
+        assert_eq!(
+            legacy
+                .source_color(&VarColor::new("--alarm").unwrap())
+                .map(|color| color.as_str()),
+            Some("#cc0000")
+        );
+        assert_eq!(
+            rules.iter().next(),
+            Some(
+                &mapping("#000000", "--pipe")
+                    .with_property(PaintProperty::Stroke)
+                    .with_selector(Selector::new("path").unwrap())
+            )
+        );
+    }
+}
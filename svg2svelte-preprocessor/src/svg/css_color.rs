@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
 pub struct CssColor(String);
 
 impl CssColor {
@@ -33,6 +33,7 @@ impl CssColor {
     }
 }
 
+#[cfg(test)]
 mod test {
     use super::CssColor;
 
@@ -0,0 +1,794 @@
+use anyhow::{anyhow, Result};
+use std::fmt::Display;
+
+/**
+ * A single node of a parsed SVG document.
+ * Text and attribute values are kept exactly as they appear in the source.
+ * Expressions are never parsed; they are generated Svelte `{...}` tags.
+ */
+#[derive(Debug, PartialEq, Clone)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+    Comment(String),
+    CData(String),
+    ProcessingInstruction(String),
+    Doctype(String),
+    Expression(String),
+}
+
+/**
+ * Quote used for attributes added by the preprocessor.
+ */
+const DEFAULT_QUOTE: char = '\'';
+
+/**
+ * An attribute of an element. Expression attributes are generated Svelte
+ * attributes or directives whose value is written unquoted in braces.
+ */
+#[derive(Debug, PartialEq, Clone)]
+pub struct Attribute {
+    name: String,
+    value: String,
+    quote: char,
+    expression: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Element {
+    name: String,
+    attributes: Vec<Attribute>,
+    children: Vec<Node>,
+    self_closing: bool,
+}
+
+/**
+ * A parsed SVG document with exactly one root `<svg>` element.
+ */
+#[derive(Debug, PartialEq, Clone)]
+pub struct Document {
+    nodes: Vec<Node>,
+    root_index: usize,
+}
+
+impl Attribute {
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn value(&self) -> &str {
+        self.value.as_str()
+    }
+
+    pub fn quote(&self) -> char {
+        self.quote
+    }
+
+    pub fn is_expression(&self) -> bool {
+        self.expression
+    }
+
+    /**
+     * The value with its quote character replaced by a character reference, as it is
+     * written between the quotes.
+     */
+    pub fn quoted_value(&self) -> String {
+        match self.quote {
+            '"' => self.value.replace('"', "&quot;"),
+            _ => self.value.replace('\'', "&apos;"),
+        }
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.to_string();
+    }
+}
+
+impl Element {
+    /**
+     * An empty element added by the preprocessor.
+     */
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+            self_closing: false,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /**
+     * Sets an attribute, keeping the position and quoting of an existing one.
+     */
+    pub fn set_attribute(&mut self, name: &str, value: &str) {
+        match self
+            .attributes
+            .iter_mut()
+            .find(|attribute| attribute.name == name)
+        {
+            Some(attribute) => attribute.set_value(value),
+            None => self.attributes.push(Attribute {
+                name: name.to_string(),
+                value: value.to_string(),
+                quote: DEFAULT_QUOTE,
+                expression: false,
+            }),
+        }
+    }
+
+    /**
+     * Sets an attribute to a Svelte expression, replacing a literal attribute of the same name in place.
+     */
+    pub fn set_expression_attribute(&mut self, name: &str, expression: &str) {
+        match self
+            .attributes
+            .iter_mut()
+            .find(|attribute| attribute.name == name)
+        {
+            Some(attribute) => {
+                attribute.value = expression.to_string();
+                attribute.expression = true;
+            }
+            None => self.attributes.push(Attribute {
+                name: name.to_string(),
+                value: expression.to_string(),
+                quote: DEFAULT_QUOTE,
+                expression: true,
+            }),
+        }
+    }
+
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    pub fn attributes_mut(&mut self) -> impl Iterator<Item = &mut Attribute> {
+        self.attributes.iter_mut()
+    }
+
+    pub fn retain_attributes(&mut self, keep: impl FnMut(&Attribute) -> bool) {
+        self.attributes.retain(keep);
+    }
+
+    /**
+     * The class names in the `class` attribute.
+     */
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        self.attribute("class").unwrap_or("").split_whitespace()
+    }
+
+    pub fn children(&self) -> &[Node] {
+        &self.children
+    }
+
+    pub fn retain_children(&mut self, keep: impl FnMut(&Node) -> bool) {
+        self.children.retain(keep);
+    }
+
+    pub fn children_mut(&mut self) -> &mut Vec<Node> {
+        &mut self.children
+    }
+
+    pub fn is_self_closing(&self) -> bool {
+        self.self_closing && self.children.is_empty()
+    }
+
+    pub fn child_elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            _ => None,
+        })
+    }
+
+    /**
+     * Looks up a presentation property, preferring a declaration in the
+     * inline `style` attribute over the presentation attribute of the same name.
+     */
+    pub fn property(&self, name: &str) -> Option<&str> {
+        let from_style = self.attribute("style").and_then(|style| {
+            style
+                .split(';')
+                .rev()
+                .filter_map(|declaration| declaration.split_once(':'))
+                .find(|(property, _)| property.trim() == name)
+                .map(|(_, value)| value.trim())
+        });
+
+        from_style.or_else(|| self.attribute(name))
+    }
+
+    /**
+     * Concatenates all descendant text, with surrounding whitespace trimmed.
+     */
+    pub fn text_content(&self) -> String {
+        fn collect(element: &Element, text: &mut String) {
+            for child in element.children.iter() {
+                match child {
+                    Node::Text(value) | Node::CData(value) => text.push_str(value),
+                    Node::Element(element) => collect(element, text),
+                    _ => {}
+                }
+            }
+        }
+
+        let mut text = String::new();
+        collect(self, &mut text);
+
+        text.trim().to_string()
+    }
+
+    /**
+     * Visits this element and every descendant element in document (paint) order.
+     * The callback also receives the ancestors of each element, outermost first.
+     */
+    pub fn walk<'a>(&'a self, visitor: &mut impl FnMut(&'a Element, &[&'a Element])) {
+        fn visit<'a>(
+            element: &'a Element,
+            ancestors: &mut Vec<&'a Element>,
+            visitor: &mut impl FnMut(&'a Element, &[&'a Element]),
+        ) {
+            visitor(element, ancestors);
+
+            ancestors.push(element);
+
+            for child in element.child_elements() {
+                visit(child, ancestors, visitor);
+            }
+
+            ancestors.pop();
+        }
+
+        visit(self, &mut Vec::new(), visitor);
+    }
+
+    /**
+     * Like `walk`, but with mutable access to each element.
+     * Ancestors are passed without their children, which are being visited.
+     */
+    pub fn walk_mut(&mut self, visitor: &mut impl FnMut(&mut Element, &[&Element])) {
+        fn visit(
+            element: &mut Element,
+            ancestors: &[&Element],
+            visitor: &mut impl FnMut(&mut Element, &[&Element]),
+        ) {
+            visitor(element, ancestors);
+
+            let mut children = std::mem::take(&mut element.children);
+
+            if children
+                .iter()
+                .any(|child| matches!(child, Node::Element(_)))
+            {
+                let mut path = Vec::with_capacity(ancestors.len() + 1);
+
+                path.extend_from_slice(ancestors);
+                path.push(&*element);
+
+                for child in children.iter_mut() {
+                    if let Node::Element(child) = child {
+                        visit(child, &path, visitor);
+                    }
+                }
+            }
+
+            element.children = children;
+        }
+
+        visit(self, &[], visitor);
+    }
+}
+
+impl Display for Attribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.expression {
+            return write!(f, "{}={{{}}}", self.name, self.value);
+        }
+
+        write!(
+            f,
+            "{}={}{}{}",
+            self.name,
+            self.quote,
+            self.quoted_value(),
+            self.quote
+        )
+    }
+}
+
+impl Display for Element {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{}", self.name)?;
+
+        for attribute in self.attributes.iter() {
+            write!(f, " {}", attribute)?;
+        }
+
+        if self.is_self_closing() {
+            return write!(f, "/>");
+        }
+
+        write!(f, ">")?;
+
+        for child in self.children.iter() {
+            write!(f, "{}", child)?;
+        }
+
+        write!(f, "</{}>", self.name)
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Node::Element(element) => write!(f, "{}", element),
+            Node::Text(text) => write!(f, "{}", text),
+            Node::Comment(comment) => write!(f, "<!--{}-->", comment),
+            Node::CData(data) => write!(f, "<![CDATA[{}]]>", data),
+            Node::ProcessingInstruction(instruction) => write!(f, "<?{}?>", instruction),
+            Node::Doctype(doctype) => write!(f, "<!{}>", doctype),
+            Node::Expression(expression) => write!(f, "{{{}}}", expression),
+        }
+    }
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for node in self.nodes.iter() {
+            write!(f, "{}", node)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Document {
+    pub fn parse(raw_svg_text: &str) -> Result<Self> {
+        DocumentParser::new(raw_svg_text).parse()
+    }
+
+    /**
+     * The root `<svg>` element of the document.
+     */
+    pub fn root(&self) -> &Element {
+        match &self.nodes[self.root_index] {
+            Node::Element(element) => element,
+            _ => unreachable!("The root index always refers to an element."),
+        }
+    }
+
+    pub fn root_mut(&mut self) -> &mut Element {
+        match &mut self.nodes[self.root_index] {
+            Node::Element(element) => element,
+            _ => unreachable!("The root index always refers to an element."),
+        }
+    }
+}
+
+/**
+ * Decodes the predefined XML entities and numeric character references in raw text.
+ * Unknown entities are kept as written.
+ */
+pub fn decode_entities(raw: &str) -> String {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => entity
+                    .strip_prefix('#')
+                    .and_then(|dec| dec.parse::<u32>().ok()),
+            }
+            .and_then(char::from_u32),
+        });
+
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+struct DocumentParser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> DocumentParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, position: 0 }
+    }
+
+    fn parse(mut self) -> Result<Document> {
+        let mut stack: Vec<Element> = Vec::new();
+        let mut nodes: Vec<Node> = Vec::new();
+
+        while self.position < self.text.len() {
+            let rest = self.rest();
+
+            let node = if rest.starts_with("<!--") {
+                Node::Comment(self.delimited("<!--", "-->")?.to_string())
+            } else if rest.starts_with("<![CDATA[") {
+                Node::CData(self.delimited("<![CDATA[", "]]>")?.to_string())
+            } else if rest.starts_with("<?") {
+                Node::ProcessingInstruction(self.delimited("<?", "?>")?.to_string())
+            } else if rest.starts_with("<!") {
+                Node::Doctype(self.doctype()?.to_string())
+            } else if rest.starts_with("</") {
+                let name = self.closing_tag()?;
+                let element = stack.pop().ok_or_else(|| {
+                    self.error(format!("Unexpected closing tag </{}>", name).as_str())
+                })?;
+
+                if element.name != name {
+                    return Err(self.error(
+                        format!("Closing tag </{}> does not match <{}>", name, element.name)
+                            .as_str(),
+                    ));
+                }
+
+                Node::Element(element)
+            } else if rest.starts_with('<') {
+                let (element, self_closing) = self.opening_tag()?;
+
+                if !self_closing {
+                    stack.push(element);
+                    continue;
+                }
+
+                Node::Element(element)
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                self.position += end;
+
+                Node::Text(rest[..end].to_string())
+            };
+
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(node),
+                None => nodes.push(node),
+            }
+        }
+
+        if let Some(element) = stack.last() {
+            return Err(anyhow!("Element <{}> is never closed.", element.name));
+        }
+
+        let root_index = nodes
+            .iter()
+            .position(|node| matches!(node, Node::Element(element) if element.name == "svg"))
+            .ok_or_else(|| anyhow!("No root <svg> element found in provided SVG text."))?;
+
+        Ok(Document { nodes, root_index })
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        let line = self.text[..self.position].matches('\n').count() + 1;
+
+        anyhow!("{} on line {}.", message, line)
+    }
+
+    fn delimited(&mut self, open: &str, close: &str) -> Result<&'a str> {
+        let start = self.position + open.len();
+        let length = self.text[start..]
+            .find(close)
+            .ok_or_else(|| self.error(format!("Unterminated {}", open).as_str()))?;
+
+        self.position = start + length + close.len();
+
+        Ok(&self.text[start..start + length])
+    }
+
+    fn doctype(&mut self) -> Result<&'a str> {
+        let start = self.position + 2;
+        let mut depth = 0;
+
+        for (offset, c) in self.text[start..].char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                '>' if depth == 0 => {
+                    self.position = start + offset + 1;
+                    return Ok(&self.text[start..start + offset]);
+                }
+                _ => {}
+            }
+        }
+
+        Err(self.error("Unterminated <!"))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn name(&mut self) -> Result<&'a str> {
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/' | '<'))
+            .unwrap_or(rest.len());
+
+        if end == 0 {
+            return Err(self.error("Expected a name"));
+        }
+
+        self.position += end;
+
+        Ok(&rest[..end])
+    }
+
+    fn closing_tag(&mut self) -> Result<&'a str> {
+        self.position += 2;
+        let name = self.name()?;
+        self.skip_whitespace();
+
+        if !self.rest().starts_with('>') {
+            return Err(self.error(format!("Malformed closing tag </{}", name).as_str()));
+        }
+
+        self.position += 1;
+
+        Ok(name)
+    }
+
+    fn opening_tag(&mut self) -> Result<(Element, bool)> {
+        self.position += 1;
+
+        let mut element = Element {
+            name: self.name()?.to_string(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+            self_closing: false,
+        };
+
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+
+            if rest.starts_with("/>") {
+                self.position += 2;
+                element.self_closing = true;
+                return Ok((element, true));
+            }
+
+            if rest.starts_with('>') {
+                self.position += 1;
+                return Ok((element, false));
+            }
+
+            if rest.is_empty() {
+                return Err(self.error(format!("Unterminated tag <{}", element.name).as_str()));
+            }
+
+            let name = self.name()?.to_string();
+            self.skip_whitespace();
+
+            if !self.rest().starts_with('=') {
+                return Err(self.error(format!("Attribute {} has no value", name).as_str()));
+            }
+
+            self.position += 1;
+            self.skip_whitespace();
+
+            let quote = match self.rest().chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(self.error(format!("Attribute {} is not quoted", name).as_str())),
+            };
+
+            let value = self.delimited(quote.to_string().as_str(), quote.to_string().as_str())?;
+
+            element.attributes.push(Attribute {
+                name,
+                value: value.to_string(),
+                quote,
+                expression: false,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode_entities, Document, Element, Node};
+
+    #[test]
+    fn test_parse_nested_elements() {
+        let input = r#"<?xml version="1.0"?><!-- hi --><svg width="10"><g id='a'><rect fill="red"/></g></svg>"#;
+        let document = Document::parse(input).unwrap();
+        let root = document.root();
+
+        assert_eq!(document.nodes.len(), 3);
+        assert_eq!(root.attribute("width"), Some("10"));
+
+        let group = root.child_elements().next().unwrap();
+        let rect = group.child_elements().next().unwrap();
+
+        assert_eq!(group.attribute("id"), Some("a"));
+        assert_eq!(rect.name(), "rect");
+        assert_eq!(rect.attribute("fill"), Some("red"));
+    }
+
+    #[test]
+    fn test_parse_text_and_doctype() {
+        let input =
+            "<!DOCTYPE svg [<!ENTITY a 'b'>]><svg><text>Hi <tspan>there</tspan></text></svg>";
+        let document = Document::parse(input).unwrap();
+        let text = document.root().child_elements().next().unwrap();
+
+        assert!(matches!(document.nodes[0], Node::Doctype(_)));
+        assert_eq!(text.text_content(), "Hi there");
+    }
+
+    #[test]
+    fn test_property_prefers_style() {
+        let input = r#"<svg fill="red" style="stroke: blue; fill: #000"></svg>"#;
+        let document = Document::parse(input).unwrap();
+        let root = document.root();
+
+        assert_eq!(root.property("fill"), Some("#000"));
+        assert_eq!(root.property("stroke"), Some("blue"));
+        assert_eq!(root.property("opacity"), None);
+    }
+
+    #[test]
+    fn test_walk_visits_in_paint_order() {
+        let input = r#"<svg><g id="a"><rect id="b"/></g><rect id="c"/></svg>"#;
+        let document = Document::parse(input).unwrap();
+        let mut visited = Vec::new();
+
+        document.root().walk(&mut |element, ancestors| {
+            visited.push((element.attribute("id"), ancestors.len()));
+        });
+
+        assert_eq!(
+            visited,
+            vec![(None, 0), (Some("a"), 1), (Some("b"), 2), (Some("c"), 1)]
+        );
+    }
+
+    #[test]
+    fn test_mismatched_tags_are_err() {
+        assert!(Document::parse("<svg><g></svg>").is_err());
+        assert!(Document::parse("<svg>").is_err());
+        assert!(Document::parse("<svg></svg></g>").is_err());
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &amp;&#123;&#x7D;"),
+            "a <b> &{}"
+        );
+        assert_eq!(decode_entities("&unknown; & &;"), "&unknown; & &;");
+    }
+
+    #[test]
+    fn test_svg_no_attributes_is_ok() {
+        let document = Document::parse("<svg></svg>").unwrap();
+
+        assert!(document.root().attributes().is_empty());
+    }
+
+    #[test]
+    fn test_parse_open_tag_overall_text_equals_svg_section() {
+        let overall = r#"<!-- COMMENT --><svg class='tst' name="soarfan"><!-- COMMENT --></svg>"#;
+        let expected = r#"<svg class='tst' name="soarfan">"#;
+        let document = Document::parse(overall).unwrap();
+
+        assert!(document.root().to_string().starts_with(expected));
+    }
+
+    #[test]
+    fn test_attributes_are_parsed_correctly() {
+        let input = r#"<svg class='hello' name="SOAR"></svg>"#;
+        let document = Document::parse(input).unwrap();
+        let root = document.root();
+
+        assert_eq!(root.attributes().len(), 2);
+        assert_eq!(root.attribute("class"), Some("hello"));
+        assert_eq!(root.attribute("name"), Some("SOAR"));
+    }
+
+    #[test]
+    fn test_non_svg_is_err() {
+        let input = r#"<div class="test"></div>"#;
+
+        assert!(Document::parse(input).is_err());
+    }
+
+    #[test]
+    fn test_only_svg_closing_is_err() {
+        let input = r#"</svg>"#;
+
+        assert!(Document::parse(input).is_err());
+    }
+
+    #[test]
+    fn test_serialize_no_attributes() {
+        let element = Element::new("svg");
+
+        assert_eq!(element.to_string(), "<svg></svg>");
+    }
+
+    #[test]
+    fn test_serialize_with_attributes() {
+        let mut element = Element::new("svg");
+        element.set_attribute("class", "SOAR");
+        element.set_attribute("name", "fan");
+
+        assert_eq!(element.to_string(), "<svg class='SOAR' name='fan'></svg>");
+    }
+
+    #[test]
+    fn test_serialize_round_trips() {
+        let input = r#"<?xml version="1.0"?>
+<!DOCTYPE svg><svg width="10" class='a b'><!-- c --><g><path d="M0 0"/></g><style><![CDATA[.a{}]]></style></svg>
+"#;
+        let document = Document::parse(input).unwrap();
+
+        assert_eq!(document.to_string(), input);
+    }
+
+    #[test]
+    fn test_set_attribute_and_walk_mut() {
+        let input = r#"<svg><g id="a"><rect fill="red"/></g></svg>"#;
+        let mut document = Document::parse(input).unwrap();
+
+        let mut depths = Vec::new();
+
+        document.root_mut().walk_mut(&mut |element, ancestors| {
+            depths.push(ancestors.len());
+
+            if element.name() == "rect" {
+                let parent_id = ancestors.last().and_then(|parent| parent.attribute("id"));
+
+                element.set_attribute("fill", parent_id.unwrap_or(""));
+                element.set_attribute("data-x", "it's");
+            }
+        });
+
+        assert_eq!(
+            document.to_string(),
+            r#"<svg><g id="a"><rect fill="a" data-x='it&apos;s'/></g></svg>"#
+        );
+        assert_eq!(depths, vec![0, 1, 2]);
+    }
+}
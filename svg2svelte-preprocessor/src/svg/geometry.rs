@@ -0,0 +1,1043 @@
+use super::document::Element;
+use anyhow::{anyhow, Result};
+use std::f64::consts::PI;
+
+/**
+ * An axis-aligned bounding box in user units.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+}
+
+/**
+ * A 2D affine transform `[a, b, c, d, e, f]`, mapping `(x, y)` to
+ * `(a * x + c * y + e, b * x + d * y + f)` as in the SVG `matrix()` function.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform([f64; 6]);
+
+pub(super) type Point = (f64, f64);
+
+/**
+ * One piece of an outline. Arcs are elliptical arcs in center parameterization,
+ * with the x axis rotation and angles in radians.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Segment {
+    Line(Point, Point),
+    Quadratic(Point, Point, Point),
+    Cubic(Point, Point, Point, Point),
+    Arc {
+        center: Point,
+        radii: Point,
+        rotation: f64,
+        start_angle: f64,
+        sweep_angle: f64,
+    },
+}
+
+impl BoundingBox {
+    pub fn new(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Self {
+        Self {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    pub fn min_x(&self) -> f64 {
+        self.min_x
+    }
+
+    pub fn min_y(&self) -> f64 {
+        self.min_y
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new(
+            self.min_x.min(other.min_x),
+            self.min_y.min(other.min_y),
+            self.max_x.max(other.max_x),
+            self.max_y.max(other.max_y),
+        )
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        (self.min_x..=self.max_x).contains(&x) && (self.min_y..=self.max_y).contains(&y)
+    }
+
+    /**
+     * Bounds of a shape element (`path`, `rect`, `circle`, `ellipse`, `line`, `polyline`, `polygon`)
+     * after its own `transform` and then the given transform of its ancestors are applied.
+     * Strokes are not included, like `getBBox()`.
+     */
+    pub fn of_shape(element: &Element, transform: &Transform) -> Option<Self> {
+        let transform = transform.multiply(&Transform::of_element(element)?);
+
+        shape_segments(element)?
+            .iter()
+            .map(|segment| segment.bounds(&transform))
+            .reduce(|bounds, segment| bounds.union(&segment))
+    }
+
+    /**
+     * Bounds of a shape, of an `image` or `foreignObject` from its `x`, `y`, `width` and
+     * `height`, of the anchor point of a text, or of everything inside a group or nested
+     * `<svg>`, in the coordinates of the given transform of the ancestors. A `<use>` has
+     * no bounds, as what it draws is defined elsewhere in the document.
+     */
+    pub fn of_element(element: &Element, transform: &Transform) -> Option<Self> {
+        match element.name() {
+            "text" | "tspan" => {
+                let transform = transform.multiply(&Transform::of_element(element)?);
+                let (x, y) = transform.apply(text_anchor(element));
+
+                Some(Self::new(x, y, x, y))
+            }
+            "image" | "foreignObject" => {
+                let transform = transform.multiply(&Transform::of_element(element)?);
+                let number = |name: &str| length(element.attribute(name)).unwrap_or(0.0);
+                let (x, y) = (number("x"), number("y"));
+                let (width, height) = (
+                    length(element.attribute("width"))?,
+                    length(element.attribute("height"))?,
+                );
+
+                if width <= 0.0 || height <= 0.0 {
+                    return None;
+                }
+
+                Self::of_points(
+                    &[
+                        (x, y),
+                        (x + width, y),
+                        (x + width, y + height),
+                        (x, y + height),
+                    ]
+                    .map(|corner| transform.apply(corner)),
+                )
+            }
+            "g" | "a" | "switch" => Self::of_children(
+                element,
+                &transform.multiply(&Transform::of_element(element)?),
+            ),
+            "svg" => Self::of_children(
+                element,
+                &transform.multiply(&Transform::of_viewport(element)?),
+            ),
+            _ => Self::of_shape(element, transform),
+        }
+    }
+
+    /**
+     * Bounds of an element in root viewBox coordinates, given its ancestors from outermost to innermost.
+     */
+    pub fn in_root(element: &Element, ancestors: &[&Element]) -> Option<Self> {
+        match ancestors.is_empty() {
+            true => Self::of_children(element, &Transform::default()),
+            false => Self::of_element(element, &Transform::of_ancestors(ancestors)?),
+        }
+    }
+
+    fn of_children(element: &Element, transform: &Transform) -> Option<Self> {
+        element
+            .child_elements()
+            .filter_map(|child| Self::of_element(child, transform))
+            .reduce(|bounds, child| bounds.union(&child))
+    }
+
+    fn of_points(points: &[Point]) -> Option<Self> {
+        let (&(x, y), rest) = points.split_first()?;
+
+        Some(rest.iter().fold(Self::new(x, y, x, y), |bounds, &(x, y)| {
+            Self::new(
+                bounds.min_x.min(x),
+                bounds.min_y.min(y),
+                bounds.max_x.max(x),
+                bounds.max_y.max(y),
+            )
+        }))
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0])
+    }
+}
+
+impl Transform {
+    /**
+     * Parses a `transform` attribute such as `translate(10 20) rotate(45, 5, 5)`.
+     */
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut transform = Self::default();
+        let mut rest = text.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+
+        while !rest.is_empty() {
+            let (name, arguments) = rest
+                .split_once('(')
+                .ok_or(anyhow!("Invalid transform {}.", text))?;
+            let (arguments, remaining) = arguments
+                .split_once(')')
+                .ok_or(anyhow!("Unterminated transform {}.", text))?;
+
+            let arguments = numbers(arguments)?;
+            let function = match (name.trim(), arguments.as_slice()) {
+                ("matrix", &[a, b, c, d, e, f]) => Self([a, b, c, d, e, f]),
+                ("translate", &[x]) => Self([1.0, 0.0, 0.0, 1.0, x, 0.0]),
+                ("translate", &[x, y]) => Self([1.0, 0.0, 0.0, 1.0, x, y]),
+                ("scale", &[x]) => Self([x, 0.0, 0.0, x, 0.0, 0.0]),
+                ("scale", &[x, y]) => Self([x, 0.0, 0.0, y, 0.0, 0.0]),
+                ("rotate", &[angle]) => Self::rotation(angle),
+                ("rotate", &[angle, x, y]) => Self([1.0, 0.0, 0.0, 1.0, x, y])
+                    .multiply(&Self::rotation(angle))
+                    .multiply(&Self([1.0, 0.0, 0.0, 1.0, -x, -y])),
+                ("skewX", &[angle]) => Self([1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0]),
+                ("skewY", &[angle]) => Self([1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0]),
+                _ => return Err(anyhow!("Invalid transform function {}.", name.trim())),
+            };
+
+            transform = transform.multiply(&function);
+            rest = remaining.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        }
+
+        Ok(transform)
+    }
+
+    /**
+     * The transform of an element, or `None` when its `transform` attribute is invalid.
+     */
+    pub fn of_element(element: &Element) -> Option<Self> {
+        match element.attribute("transform") {
+            Some(transform) => Self::parse(transform).ok(),
+            None => Some(Self::default()),
+        }
+    }
+
+    /**
+     * The combined transform of the ancestors of an element, outermost first.
+     * The root `<svg>` establishes the viewBox coordinates and is not transformed itself,
+     * while a nested `<svg>` places its content by its viewport.
+     */
+    pub fn of_ancestors(ancestors: &[&Element]) -> Option<Self> {
+        ancestors
+            .iter()
+            .enumerate()
+            .try_fold(Self::default(), |transform, (index, ancestor)| {
+                let own = match (index, ancestor.name()) {
+                    (0, "svg") => Self::default(),
+                    (_, "svg") => Self::of_viewport(ancestor)?,
+                    _ => Self::of_element(ancestor)?,
+                };
+
+                Some(transform.multiply(&own))
+            })
+    }
+
+    /**
+     * The transform a nested `<svg>` applies to its content: its `x` and `y` offset and the
+     * fit of its `viewBox` into its `width` and `height` by `preserveAspectRatio`. `None` when
+     * a length is relative, or the viewBox is invalid or has no absolute size to fit into.
+     */
+    pub fn of_viewport(element: &Element) -> Option<Self> {
+        let offset = |name: &str| match element.attribute(name) {
+            Some(value) => absolute_length(value).ok(),
+            None => Some(0.0),
+        };
+        let (x, y) = (offset("x")?, offset("y")?);
+
+        let Some(view_box) = element.attribute("viewBox") else {
+            return Some(Self([1.0, 0.0, 0.0, 1.0, x, y]));
+        };
+
+        let [min_x, min_y, view_width, view_height]: [f64; 4] =
+            numbers(view_box).ok()?.try_into().ok()?;
+
+        if view_width <= 0.0 || view_height <= 0.0 {
+            return None;
+        }
+
+        let width = absolute_length(element.attribute("width")?).ok()?;
+        let height = absolute_length(element.attribute("height")?).ok()?;
+        let (mut scale_x, mut scale_y) = (width / view_width, height / view_height);
+        let (mut align_x, mut align_y) = (0.0, 0.0);
+
+        let mut preserve = element
+            .attribute("preserveAspectRatio")
+            .unwrap_or_default()
+            .split_whitespace()
+            .skip_while(|&part| part == "defer");
+        let align = preserve.next().unwrap_or("xMidYMid");
+
+        if align != "none" {
+            let scale = match preserve.next() {
+                Some("slice") => scale_x.max(scale_y),
+                _ => scale_x.min(scale_y),
+            };
+            let fraction = |axis: &str| match align {
+                _ if align.contains(format!("{}Min", axis).as_str()) => 0.0,
+                _ if align.contains(format!("{}Max", axis).as_str()) => 1.0,
+                _ => 0.5,
+            };
+
+            align_x = (width - view_width * scale) * fraction("x");
+            align_y = (height - view_height * scale) * fraction("Y");
+            (scale_x, scale_y) = (scale, scale);
+        }
+
+        Some(Self([
+            scale_x,
+            0.0,
+            0.0,
+            scale_y,
+            x + align_x - min_x * scale_x,
+            y + align_y - min_y * scale_y,
+        ]))
+    }
+
+    fn rotation(degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self([cos, sin, -sin, cos, 0.0, 0.0])
+    }
+
+    /**
+     * The transform that applies `other` first and then this one.
+     */
+    pub fn multiply(&self, other: &Self) -> Self {
+        let [a1, b1, c1, d1, e1, f1] = self.0;
+        let [a2, b2, c2, d2, e2, f2] = other.0;
+
+        Self([
+            a1 * a2 + c1 * b2,
+            b1 * a2 + d1 * b2,
+            a1 * c2 + c1 * d2,
+            b1 * c2 + d1 * d2,
+            a1 * e2 + c1 * f2 + e1,
+            b1 * e2 + d1 * f2 + f1,
+        ])
+    }
+
+    pub fn apply(&self, (x, y): Point) -> Point {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+}
+
+impl Segment {
+    /**
+     * Exact bounds of the segment after a transform. Affine transforms map lines and Bézier
+     * curves to curves of the same kind, so the extrema are found on the transformed curve.
+     */
+    fn bounds(&self, transform: &Transform) -> BoundingBox {
+        let mut points = Vec::new();
+
+        match *self {
+            Segment::Line(p0, p1) => {
+                points.extend([transform.apply(p0), transform.apply(p1)]);
+            }
+            Segment::Quadratic(p0, p1, p2) => {
+                let [p0, p1, p2] = [p0, p1, p2].map(|point| transform.apply(point));
+                let axes = [(p0.0, p1.0, p2.0), (p0.1, p1.1, p2.1)];
+
+                points.extend([p0, p2]);
+
+                for (q0, q1, q2) in axes {
+                    let denominator = q0 - 2.0 * q1 + q2;
+
+                    if denominator != 0.0 {
+                        points.extend(
+                            parameter_in_curve((q0 - q1) / denominator)
+                                .map(|t| quadratic_point(p0, p1, p2, t)),
+                        );
+                    }
+                }
+            }
+            Segment::Cubic(p0, p1, p2, p3) => {
+                let [p0, p1, p2, p3] = [p0, p1, p2, p3].map(|point| transform.apply(point));
+                let axes = [(p0.0, p1.0, p2.0, p3.0), (p0.1, p1.1, p2.1, p3.1)];
+
+                points.extend([p0, p3]);
+
+                for (q0, q1, q2, q3) in axes {
+                    let a = -q0 + 3.0 * q1 - 3.0 * q2 + q3;
+                    let b = 2.0 * (q0 - 2.0 * q1 + q2);
+                    let c = q1 - q0;
+
+                    points.extend(
+                        quadratic_roots(a, b, c)
+                            .into_iter()
+                            .filter_map(parameter_in_curve)
+                            .map(|t| cubic_point(p0, p1, p2, p3, t)),
+                    );
+                }
+            }
+            Segment::Arc {
+                center,
+                radii,
+                rotation,
+                start_angle,
+                sweep_angle,
+            } => {
+                let (sin, cos) = rotation.sin_cos();
+                let ellipse = Transform([
+                    radii.0 * cos,
+                    radii.0 * sin,
+                    -radii.1 * sin,
+                    radii.1 * cos,
+                    center.0,
+                    center.1,
+                ]);
+                let [a, b, c, d, _, _] = transform.multiply(&ellipse).0;
+                let point = |angle: f64| transform.apply(ellipse.apply((angle.cos(), angle.sin())));
+
+                points.extend([point(start_angle), point(start_angle + sweep_angle)]);
+
+                // x(t) = a cos t + c sin t and y(t) = b cos t + d sin t are extreme at atan2(c, a) and atan2(d, b).
+                for extremum in [c.atan2(a), d.atan2(b)] {
+                    for angle in [extremum, extremum + PI] {
+                        if angle_in_sweep(angle, start_angle, sweep_angle) {
+                            points.push(point(angle));
+                        }
+                    }
+                }
+            }
+        }
+
+        BoundingBox::of_points(&points).expect("Segments always have end points.")
+    }
+}
+
+/**
+ * The outline of a shape element as segments in its own coordinates.
+ */
+fn shape_segments(element: &Element) -> Option<Vec<Segment>> {
+    let number = |name: &str| length(element.attribute(name)).unwrap_or(0.0);
+
+    let path = match element.name() {
+        "path" => return path_segments(element.attribute("d")?).ok(),
+        "rect" => {
+            let (x, y, width, height) =
+                (number("x"), number("y"), number("width"), number("height"));
+
+            if width <= 0.0 || height <= 0.0 {
+                return None;
+            }
+
+            let rx = length(element.attribute("rx")).or(length(element.attribute("ry")));
+            let ry = length(element.attribute("ry")).or(rx);
+            let (rx, ry) = (
+                rx.unwrap_or(0.0).clamp(0.0, width / 2.0),
+                ry.unwrap_or(0.0).clamp(0.0, height / 2.0),
+            );
+
+            format!(
+                "M{} {}H{}A{rx} {ry} 0 0 1 {} {}V{}A{rx} {ry} 0 0 1 {} {}H{}A{rx} {ry} 0 0 1 {} {}V{}A{rx} {ry} 0 0 1 {} {}Z",
+                x + rx,
+                y,
+                x + width - rx,
+                x + width,
+                y + ry,
+                y + height - ry,
+                x + width - rx,
+                y + height,
+                x + rx,
+                x,
+                y + height - ry,
+                y + ry,
+                x + rx,
+                y,
+            )
+        }
+        "circle" | "ellipse" => {
+            let (cx, cy) = (number("cx"), number("cy"));
+            let (rx, ry) = match element.name() {
+                "circle" => (number("r"), number("r")),
+                _ => (number("rx"), number("ry")),
+            };
+
+            if rx <= 0.0 || ry <= 0.0 {
+                return None;
+            }
+
+            format!(
+                "M{} {cy}A{rx} {ry} 0 1 1 {} {cy}A{rx} {ry} 0 1 1 {} {cy}Z",
+                cx + rx,
+                cx - rx,
+                cx + rx
+            )
+        }
+        "line" => format!(
+            "M{} {}L{} {}",
+            number("x1"),
+            number("y1"),
+            number("x2"),
+            number("y2")
+        ),
+        "polyline" | "polygon" => {
+            let points = element.attribute("points")?;
+            let close = if element.name() == "polygon" { "Z" } else { "" };
+
+            format!("M{}{}", points, close)
+        }
+        _ => return None,
+    };
+
+    path_segments(path.as_str()).ok()
+}
+
+/**
+ * Parses SVG path data into segments, supporting every command in absolute and relative form.
+ */
+fn path_segments(data: &str) -> Result<Vec<Segment>> {
+    let mut scanner = PathScanner::new(data);
+    let mut segments = Vec::new();
+
+    let mut current = (0.0, 0.0);
+    let mut subpath_start = (0.0, 0.0);
+    let mut last_control: Option<(char, Point)> = None;
+    let mut command = None;
+
+    loop {
+        scanner.skip_separators();
+
+        if scanner.peek().is_none() {
+            break;
+        }
+
+        let next_command = match scanner.command() {
+            Some(c) => c,
+            None => match command {
+                Some('M') => 'L',
+                Some('m') => 'l',
+                Some('Z' | 'z') | None => {
+                    return Err(anyhow!("Path data has coordinates without a command."))
+                }
+                Some(command) => command,
+            },
+        };
+
+        command = Some(next_command);
+
+        let relative = next_command.is_ascii_lowercase();
+        let offset = |point: Point, current: Point| match relative {
+            true => (point.0 + current.0, point.1 + current.1),
+            false => point,
+        };
+
+        let reflected = |kinds: &[char], current: Point| match last_control {
+            Some((kind, control)) if kinds.contains(&kind) => {
+                (2.0 * current.0 - control.0, 2.0 * current.1 - control.1)
+            }
+            _ => current,
+        };
+
+        match next_command.to_ascii_uppercase() {
+            'M' => {
+                current = offset(scanner.point()?, current);
+                subpath_start = current;
+                last_control = None;
+            }
+            'L' => {
+                let end = offset(scanner.point()?, current);
+                segments.push(Segment::Line(current, end));
+                current = end;
+                last_control = None;
+            }
+            'H' | 'V' => {
+                let value = scanner.number()?;
+                let end = match (next_command.to_ascii_uppercase(), relative) {
+                    ('H', true) => (current.0 + value, current.1),
+                    ('H', false) => (value, current.1),
+                    (_, true) => (current.0, current.1 + value),
+                    (_, false) => (current.0, value),
+                };
+
+                segments.push(Segment::Line(current, end));
+                current = end;
+                last_control = None;
+            }
+            'C' | 'S' => {
+                let first = match next_command.to_ascii_uppercase() {
+                    'C' => offset(scanner.point()?, current),
+                    _ => reflected(&['C', 'S'], current),
+                };
+                let second = offset(scanner.point()?, current);
+                let end = offset(scanner.point()?, current);
+
+                segments.push(Segment::Cubic(current, first, second, end));
+                current = end;
+                last_control = Some(('C', second));
+            }
+            'Q' | 'T' => {
+                let control = match next_command.to_ascii_uppercase() {
+                    'Q' => offset(scanner.point()?, current),
+                    _ => reflected(&['Q'], current),
+                };
+                let end = offset(scanner.point()?, current);
+
+                segments.push(Segment::Quadratic(current, control, end));
+                current = end;
+                last_control = Some(('Q', control));
+            }
+            'A' => {
+                let radii = (scanner.number()?.abs(), scanner.number()?.abs());
+                let rotation = scanner.number()?;
+                let large_arc = scanner.flag()?;
+                let sweep = scanner.flag()?;
+                let end = offset(scanner.point()?, current);
+
+                segments.push(arc_segment(current, end, radii, rotation, large_arc, sweep));
+                current = end;
+                last_control = None;
+            }
+            'Z' => {
+                segments.push(Segment::Line(current, subpath_start));
+                current = subpath_start;
+                last_control = None;
+            }
+            _ => return Err(anyhow!("Unknown path command {}.", next_command)),
+        }
+    }
+
+    Ok(segments)
+}
+
+/**
+ * Converts an arc from endpoint to center parameterization (SVG 2, appendix B.2.4),
+ * scaling radii that are too small to reach the end point.
+ */
+fn arc_segment(
+    start: Point,
+    end: Point,
+    radii: Point,
+    rotation_degrees: f64,
+    large_arc: bool,
+    sweep: bool,
+) -> Segment {
+    let (mut rx, mut ry) = radii;
+
+    if start == end || rx == 0.0 || ry == 0.0 {
+        return Segment::Line(start, end);
+    }
+
+    let rotation = rotation_degrees.to_radians();
+    let (sin, cos) = rotation.sin_cos();
+
+    let (dx, dy) = ((start.0 - end.0) / 2.0, (start.1 - end.1) / 2.0);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+
+    let scale = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+
+    if scale > 1.0 {
+        rx *= scale.sqrt();
+        ry *= scale.sqrt();
+    }
+
+    let numerator = (rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1).max(0.0);
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coefficient = sign * (numerator / denominator).sqrt();
+
+    let (cx1, cy1) = (coefficient * rx * y1 / ry, -coefficient * ry * x1 / rx);
+    let center = (
+        cos * cx1 - sin * cy1 + (start.0 + end.0) / 2.0,
+        sin * cx1 + cos * cy1 + (start.1 + end.1) / 2.0,
+    );
+
+    let start_angle = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+    let end_angle = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx);
+    let mut sweep_angle = end_angle - start_angle;
+
+    if sweep && sweep_angle < 0.0 {
+        sweep_angle += 2.0 * PI;
+    } else if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= 2.0 * PI;
+    }
+
+    Segment::Arc {
+        center,
+        radii: (rx, ry),
+        rotation,
+        start_angle,
+        sweep_angle,
+    }
+}
+
+/**
+ * Reads the numbers and flags of SVG path data.
+ */
+pub(super) struct PathScanner<'a> {
+    data: &'a str,
+    position: usize,
+}
+
+impl<'a> PathScanner<'a> {
+    pub(super) fn new(data: &'a str) -> Self {
+        Self { data, position: 0 }
+    }
+
+    pub(super) fn peek(&self) -> Option<char> {
+        self.data[self.position..].chars().next()
+    }
+
+    /**
+     * Consumes the command letter at the current position, if there is one.
+     */
+    pub(super) fn command(&mut self) -> Option<char> {
+        let command = self.peek().filter(char::is_ascii_alphabetic)?;
+        self.position += 1;
+        Some(command)
+    }
+
+    pub(super) fn skip_separators(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace() || c == ',') {
+            self.position += 1;
+        }
+    }
+
+    /**
+     * Reads a number, which may directly follow the previous one as in `1.5.5` or `10-5`.
+     */
+    pub(super) fn number(&mut self) -> Result<f64> {
+        self.skip_separators();
+
+        let rest = &self.data[self.position..];
+        let bytes = rest.as_bytes();
+        let mut end = 0;
+
+        if matches!(bytes.first(), Some(b'+' | b'-')) {
+            end += 1;
+        }
+
+        let digits = |end: &mut usize| {
+            let start = *end;
+            while bytes.get(*end).is_some_and(u8::is_ascii_digit) {
+                *end += 1;
+            }
+            *end > start
+        };
+
+        let mut has_digits = digits(&mut end);
+
+        if bytes.get(end) == Some(&b'.') {
+            end += 1;
+            has_digits |= digits(&mut end);
+        }
+
+        if has_digits && matches!(bytes.get(end), Some(b'e' | b'E')) {
+            let mut exponent_end = end + 1;
+
+            if matches!(bytes.get(exponent_end), Some(b'+' | b'-')) {
+                exponent_end += 1;
+            }
+
+            if digits(&mut exponent_end) {
+                end = exponent_end;
+            }
+        }
+
+        let number = rest[..end]
+            .parse::<f64>()
+            .map_err(|_| anyhow!("Expected a number in path data at {}.", self.position))?;
+
+        self.position += end;
+        Ok(number)
+    }
+
+    pub(super) fn point(&mut self) -> Result<Point> {
+        Ok((self.number()?, self.number()?))
+    }
+
+    /**
+     * Reads an arc flag, which is a single `0` or `1` that may be followed directly by the next value.
+     */
+    pub(super) fn flag(&mut self) -> Result<bool> {
+        self.skip_separators();
+
+        let flag = match self.peek() {
+            Some('0') => false,
+            Some('1') => true,
+            _ => {
+                return Err(anyhow!(
+                    "Expected an arc flag in path data at {}.",
+                    self.position
+                ))
+            }
+        };
+
+        self.position += 1;
+        Ok(flag)
+    }
+}
+
+/**
+ * Parses a list of numbers separated by whitespace or commas.
+ */
+fn numbers(text: &str) -> Result<Vec<f64>> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse::<f64>()
+                .map_err(|_| anyhow!("Invalid number {}.", value))
+        })
+        .collect()
+}
+
+fn parameter_in_curve(t: f64) -> Option<f64> {
+    (t > 0.0 && t < 1.0).then_some(t)
+}
+
+/**
+ * Real roots of `a * t^2 + b * t + c`, falling back to the linear root when `a` is zero.
+ */
+fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        return match b.abs() < 1e-12 {
+            true => Vec::new(),
+            false => vec![-c / b],
+        };
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+
+    let root = discriminant.sqrt();
+    vec![(-b + root) / (2.0 * a), (-b - root) / (2.0 * a)]
+}
+
+fn quadratic_point(p0: Point, p1: Point, p2: Point, t: f64) -> Point {
+    let u = 1.0 - t;
+    let blend = |q0: f64, q1: f64, q2: f64| u * u * q0 + 2.0 * u * t * q1 + t * t * q2;
+
+    (blend(p0.0, p1.0, p2.0), blend(p0.1, p1.1, p2.1))
+}
+
+fn cubic_point(p0: Point, p1: Point, p2: Point, p3: Point, t: f64) -> Point {
+    let u = 1.0 - t;
+    let blend = |q0: f64, q1: f64, q2: f64, q3: f64| {
+        u * u * u * q0 + 3.0 * u * u * t * q1 + 3.0 * u * t * t * q2 + t * t * t * q3
+    };
+
+    (blend(p0.0, p1.0, p2.0, p3.0), blend(p0.1, p1.1, p2.1, p3.1))
+}
+
+/**
+ * Whether an angle lies on an arc starting at `start` and sweeping `sweep` radians in either direction.
+ */
+fn angle_in_sweep(angle: f64, start: f64, sweep: f64) -> bool {
+    let (from, span) = match sweep >= 0.0 {
+        true => (start, sweep),
+        false => (start + sweep, -sweep),
+    };
+
+    (angle - from).rem_euclid(2.0 * PI) <= span
+}
+
+/**
+ * Parses the first number of a length or coordinate list attribute such as `12`, `12px` or `10 20`.
+ */
+pub fn length(value: Option<&str>) -> Option<f64> {
+    let value = value?
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .next()?;
+
+    value.trim_end_matches("px").parse::<f64>().ok()
+}
+
+/**
+ * Converts a length in an absolute unit (`px`, `in`, `cm`, `mm`, `q`, `pt`, `pc` or none)
+ * to user units at 96 per inch. Relative units and percentages have no fixed size.
+ */
+pub fn absolute_length(value: &str) -> Result<f64> {
+    let value = value.trim();
+    let unit_start = (0..=value.len())
+        .rev()
+        .filter(|&end| value.is_char_boundary(end))
+        .find(|&end| value[..end].parse::<f64>().is_ok())
+        .unwrap_or(0);
+    let (number, unit) = value.split_at(unit_start);
+
+    let scale = match unit.to_ascii_lowercase().as_str() {
+        "" | "px" => 1.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "q" => 96.0 / 101.6,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        _ => return Err(anyhow!("Length {} is not in an absolute unit.", value)),
+    };
+
+    let number = number
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .ok_or(anyhow!("Invalid length {}.", value))?;
+
+    Ok(number * scale)
+}
+
+/**
+ * The point a `<text>` element is anchored at, taken from the text or its first `<tspan>`.
+ */
+pub fn text_anchor(element: &Element) -> Point {
+    let tspan = element
+        .child_elements()
+        .find(|child| child.name() == "tspan");
+    let coordinate = |name: &str| {
+        length(element.attribute(name))
+            .or_else(|| tspan.and_then(|tspan| length(tspan.attribute(name))))
+            .unwrap_or(0.0)
+    };
+
+    (coordinate("x"), coordinate("y"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{text_anchor, BoundingBox, Transform};
+    use crate::svg::document::Document;
+    use std::collections::HashMap;
+
+    fn first_child_bounds(input: &str) -> Option<BoundingBox> {
+        let document = Document::parse(input).unwrap();
+        let element = document.root().child_elements().next().unwrap();
+
+        BoundingBox::of_shape(element, &Transform::default())
+    }
+
+    fn assert_bounds_eq(actual: Option<BoundingBox>, expected: BoundingBox) {
+        let actual = actual.unwrap();
+        let corners = |bounds: BoundingBox| {
+            [
+                bounds.min_x(),
+                bounds.min_y(),
+                bounds.width(),
+                bounds.height(),
+            ]
+        };
+
+        for (actual, expected) in corners(actual).iter().zip(corners(expected)) {
+            assert!((actual - expected).abs() < 1e-9, "{:?}", actual);
+        }
+    }
+
+    #[test]
+    fn test_basic_shape_bounds() {
+        let rect = first_child_bounds(r#"<svg><rect x="1" y="2" width="10" height="5"/></svg>"#);
+        let circle = first_child_bounds(r#"<svg><circle cx="5" cy="5" r="2"/></svg>"#);
+        let polygon = first_child_bounds(r#"<svg><polygon points="0,0 10,4 -2,8"/></svg>"#);
+        let path = first_child_bounds(r#"<svg><path d="M0 0L10 10"/></svg>"#);
+        let rounded =
+            first_child_bounds(r#"<svg><rect x="0" y="0" width="10" height="6" rx="2"/></svg>"#);
+
+        assert_eq!(rect, Some(BoundingBox::new(1.0, 2.0, 11.0, 7.0)));
+        assert_eq!(circle, Some(BoundingBox::new(3.0, 3.0, 7.0, 7.0)));
+        assert_eq!(polygon, Some(BoundingBox::new(-2.0, 0.0, 10.0, 8.0)));
+        assert_bounds_eq(path, BoundingBox::new(0.0, 0.0, 10.0, 10.0));
+        assert_bounds_eq(rounded, BoundingBox::new(0.0, 0.0, 10.0, 6.0));
+    }
+
+    #[test]
+    fn test_path_bounds_include_curve_extremes() {
+        let cubic = first_child_bounds(r#"<svg><path d="M0 0C0 10 10 10 10 0"/></svg>"#);
+        let relative = first_child_bounds(r#"<svg><path d="m10 10q5-10 10 0t10 0h-5v5z"/></svg>"#);
+        let arc = first_child_bounds(r#"<svg><path d="M0 0A5 5 0 0 1 10 0"/></svg>"#);
+        let compact = first_child_bounds(r#"<svg><path d="M0 0a5 5 0 1010 0"/></svg>"#);
+        let small_radius = first_child_bounds(r#"<svg><path d="M0 0A1 1 0 0 0 20 0"/></svg>"#);
+
+        assert_bounds_eq(cubic, BoundingBox::new(0.0, 0.0, 10.0, 7.5));
+        assert_bounds_eq(relative, BoundingBox::new(10.0, 5.0, 30.0, 15.0));
+        assert_bounds_eq(arc, BoundingBox::new(0.0, -5.0, 10.0, 0.0));
+        assert_bounds_eq(compact, BoundingBox::new(0.0, 0.0, 10.0, 5.0));
+        assert_bounds_eq(small_radius, BoundingBox::new(0.0, 0.0, 20.0, 10.0));
+        assert_eq!(first_child_bounds(r#"<svg><path d="M0 0L"/></svg>"#), None);
+    }
+
+    #[test]
+    fn test_transforms_are_applied_to_bounds() {
+        let input = r#"<svg><g transform="translate(100 50) scale(2)"><rect id="a" x="0" y="0" width="10" height="5" transform="rotate(90)"/></g></svg>"#;
+        let document = Document::parse(input).unwrap();
+        let mut bounds = None;
+
+        document.root().walk(&mut |element, ancestors| {
+            if element.attribute("id") == Some("a") {
+                bounds = BoundingBox::in_root(element, ancestors);
+            }
+        });
+
+        assert_bounds_eq(bounds, BoundingBox::new(90.0, 50.0, 100.0, 70.0));
+        let (x, y) = Transform::parse("skewX(45)").unwrap().apply((0.0, 10.0));
+
+        assert!((x - 10.0).abs() < 1e-9 && y == 10.0);
+        assert!(Transform::parse("translate(1 2 3)").is_err());
+        assert!(Transform::parse("rotate(45").is_err());
+    }
+
+    #[test]
+    fn test_nested_viewports_and_images() {
+        let input = r##"<svg viewBox="0 0 100 100"><svg id="meet" x="10" y="20" width="40" height="20" viewBox="0 0 20 20"><rect id="a" width="20" height="10"/></svg><svg x="10" y="20" width="40" height="20" viewBox="0 0 20 20" preserveAspectRatio="none"><rect id="b" width="20" height="10"/></svg><svg x="10" y="20" width="40" height="20" viewBox="0 0 20 20" preserveAspectRatio="xMinYMax slice"><rect id="c" width="20" height="10"/></svg><svg width="50%" viewBox="0 0 1 1"><rect id="d" width="1" height="1"/></svg><image id="e" x="5" y="5" width="10" height="4" transform="rotate(90)"/><use id="f" href="#a"/></svg>"##;
+        let document = Document::parse(input).unwrap();
+        let mut bounds = HashMap::new();
+
+        document.root().walk(&mut |element, ancestors| {
+            if let Some(id) = element.attribute("id") {
+                bounds.insert(id.to_string(), BoundingBox::in_root(element, ancestors));
+            }
+        });
+
+        assert_eq!(bounds["a"], Some(BoundingBox::new(20.0, 20.0, 40.0, 30.0)));
+        assert_eq!(bounds["meet"], bounds["a"]);
+        assert_eq!(bounds["b"], Some(BoundingBox::new(10.0, 20.0, 50.0, 30.0)));
+        assert_eq!(bounds["c"], Some(BoundingBox::new(10.0, 0.0, 50.0, 20.0)));
+        assert_eq!(bounds["d"], None);
+        assert_bounds_eq(bounds["e"], BoundingBox::new(-9.0, 5.0, -5.0, 15.0));
+        assert_eq!(bounds["f"], None);
+    }
+
+    #[test]
+    fn test_group_bounds_are_union_of_children() {
+        let input = r#"<svg><g><rect x="1" y="2" width="10" height="5"/><g><circle cx="20" cy="0" r="1"/></g><path d="M0 0"/></g></svg>"#;
+
+        let document = Document::parse(input).unwrap();
+        let group = document.root().child_elements().next().unwrap();
+        let bounds = BoundingBox::of_element(group, &Transform::default()).unwrap();
+
+        assert_eq!(bounds, BoundingBox::new(1.0, -1.0, 21.0, 7.0));
+        assert_eq!((bounds.width(), bounds.height()), (20.0, 8.0));
+    }
+
+    #[test]
+    fn test_text_anchor_falls_back_to_tspan() {
+        let input = r#"<svg><text y="4"><tspan x="12 13" y="20">A</tspan></text></svg>"#;
+        let document = Document::parse(input).unwrap();
+        let text = document.root().child_elements().next().unwrap();
+
+        assert_eq!(text_anchor(text), (12.0, 4.0));
+    }
+}
@@ -1,5 +1,14 @@
+pub mod accessibility;
+pub mod color_mapping;
 pub mod css_color;
+pub mod document;
+pub mod geometry;
 pub mod modifier;
-pub mod parser;
-pub mod svg_attributes;
+pub mod optimize;
+pub mod passes;
+pub mod path_data;
+pub mod rgb;
+pub mod root_rule;
+pub mod selector;
+pub mod token;
 pub mod var_color;
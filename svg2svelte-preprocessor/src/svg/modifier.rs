@@ -1,147 +1,403 @@
-use super::svg_attributes::SvgAttributes;
-use crate::config::Config;
-use regex::Regex;
+use super::{
+    color_mapping::PAINT_ATTRIBUTES,
+    document::{Attribute, Document},
+    optimize::Optimizations,
+    passes::{
+        accessibility_metadata::AccessibilityMetadata,
+        extract_classes::ExtractClasses,
+        extract_images::{Asset, ExtractImages},
+        extract_styles::ExtractStyles,
+        map_colors::MapColors,
+        map_tokens::MapTokens,
+        root_attributes::RootAttributes,
+        strip_metadata::StripMetadata,
+    },
+};
+use crate::{config::Config, css::stylesheet::Stylesheet};
+use anyhow::{anyhow, Result};
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
+/**
+ * Pattern for matching a declaration of an inline style.
+ * Group 1: The property name.
+ * Group 2: The colon and surrounding whitespace.
+ * Group 3: The value.
+ */
+pub(super) const DECLARATION_PATTERN: &str = r#"([^:;]+)(\s*:\s*)([^;]*)"#;
+
+/**
+ * A transform of the document tree. Passes run in the order they are listed in the config,
+ * and each reports what it changed.
+ */
+pub trait Pass {
+    /**
+     * The name of the pass, as written in the config.
+     */
+    fn name(&self) -> &'static str;
+
+    /**
+     * The options of the pass, shown in the configuration summary.
+     */
+    fn options(&self) -> Option<String> {
+        None
+    }
+
+    fn run(&self, modifier: &mut Modifier) -> Result<PassReport>;
+}
+
+/**
+ * What a pass changed, as counts of changed things.
+ */
+#[derive(Debug, PartialEq)]
+pub struct PassReport {
+    name: &'static str,
+    changes: Vec<(String, usize)>,
+}
+
+impl PassReport {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            changes: Vec::new(),
+        }
+    }
+
+    /**
+     * Records a number of changes, unless there were none.
+     */
+    pub fn count(&mut self, what: &str, count: usize) {
+        if count > 0 {
+            self.changes.push((what.to_string(), count));
+        }
+    }
+}
+
+impl Display for PassReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let changes = self
+            .changes
+            .iter()
+            .map(|(what, count)| format!("{}: {}", what, count))
+            .collect::<Vec<_>>();
+
+        match changes.is_empty() {
+            true => write!(f, "{}: no changes", self.name),
+            false => write!(f, "{}: {}", self.name, changes.join(", ")),
+        }
+    }
+}
+
+/**
+ * A pass and its options, as listed under `passes` in the config.
+ */
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "pass", rename_all = "kebab-case")]
+pub enum PassConfig {
+    StripMetadata(StripMetadata),
+    RootAttributes(RootAttributes),
+    #[serde(rename = "accessibility")]
+    AccessibilityMetadata(AccessibilityMetadata),
+    MapColors(MapColors),
+    MapTokens(MapTokens),
+    ExtractStyles(ExtractStyles),
+    Optimize(Optimizations),
+    ExtractClasses(ExtractClasses),
+    ExtractImages(ExtractImages),
+}
+
+impl PassConfig {
+    /**
+     * The passes the preprocessor runs when the config lists none.
+     */
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            PassConfig::StripMetadata(StripMetadata::default()),
+            PassConfig::RootAttributes(RootAttributes::default()),
+            PassConfig::AccessibilityMetadata(AccessibilityMetadata {}),
+            PassConfig::MapColors(MapColors {}),
+            PassConfig::MapTokens(MapTokens {}),
+            PassConfig::ExtractStyles(ExtractStyles::default()),
+        ]
+    }
+
+    pub fn pass(&self) -> &dyn Pass {
+        match self {
+            PassConfig::StripMetadata(pass) => pass,
+            PassConfig::RootAttributes(pass) => pass,
+            PassConfig::AccessibilityMetadata(pass) => pass,
+            PassConfig::MapColors(pass) => pass,
+            PassConfig::MapTokens(pass) => pass,
+            PassConfig::ExtractStyles(pass) => pass,
+            PassConfig::Optimize(pass) => pass,
+            PassConfig::ExtractClasses(pass) => pass,
+            PassConfig::ExtractImages(pass) => pass,
+        }
+    }
+}
+
+impl Display for PassConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pass = self.pass();
+
+        match pass.options() {
+            Some(options) => write!(f, "{} ({})", pass.name(), options),
+            None => write!(f, "{}", pass.name()),
+        }
+    }
+}
+
+/**
+ * Runs the configured passes over a document, collecting the CSS and files they take out of it.
+ */
 pub struct Modifier<'a> {
     config: &'a Config,
-    accumulator: String,
+    document: Document,
+    style: String,
+    assets: Vec<Asset>,
 }
 
 impl<'a> Modifier<'a> {
-    pub fn new(config: &'a Config, initial_accumulator: &str) -> Self {
+    pub fn new(config: &'a Config, document: Document) -> Self {
         Self {
             config,
-            accumulator: initial_accumulator.to_string(),
+            document,
+            style: String::new(),
+            assets: Vec::new(),
         }
     }
 
-    pub fn fix_svg_open_tag(
-        &mut self,
-        original_tag_text: &str,
-        mut attributes: SvgAttributes,
-    ) -> &mut Self {
-        attributes.set_attribute("class", "diagram");
-        attributes.set_attribute("fill", "none");
-        attributes.set_attribute("xmlns", "http://www.w3.org/2000/svg");
-
-        match (
-            attributes.get_attribute("width"),
-            attributes.get_attribute("height"),
-        ) {
-            (Some(width), Some(height)) => {
-                let view_box = format!("0 0 {} {}", width, height);
-                attributes.set_attribute("viewBox", view_box.as_str());
-            }
-            _ => {}
-        }
+    /**
+     * Runs the passes of the config in order, stopping at the first that fails.
+     */
+    pub fn run(&mut self) -> Result<Vec<PassReport>> {
+        let config = self.config;
 
-        let new_attributes = attributes.serialize();
+        config
+            .passes()
+            .iter()
+            .map(|pass| {
+                let pass = pass.pass();
 
-        self.accumulator =
-            self.accumulator
-                .as_str()
-                .replacen(original_tag_text, new_attributes.as_str(), 1);
+                pass.run(self)
+                    .map_err(|error| anyhow!("{} pass: {}", pass.name(), error))
+            })
+            .collect()
+    }
 
-        self
+    #[inline(always)]
+    pub fn config(&self) -> &'a Config {
+        self.config
     }
 
-    pub fn map_colors(&mut self) -> &mut Self {
-        let replacements = self
-            .config
-            .color_mappings()
-            .iter()
-            .map(|(css_color, var_color)| {
-                (
-                    Regex::new(css_color.as_str())
-                        .expect("Failed to create regex for color mapping."),
-                    var_color,
-                )
-            })
-            .collect::<Box<[_]>>();
+    #[inline(always)]
+    pub fn document_mut(&mut self) -> &mut Document {
+        &mut self.document
+    }
+
+    /**
+     * Adds CSS to the component style, after what earlier passes added.
+     */
+    pub fn append_style(&mut self, style: &str) {
+        if style.is_empty() {
+            return;
+        }
+
+        if !self.style.is_empty() {
+            self.style.push_str("\n\n");
+        }
 
-        self.accumulator = replacements
+        self.style.push_str(style);
+    }
+
+    /**
+     * The CSS taken from the document by the passes.
+     */
+    pub fn extracted_style(&self) -> &str {
+        self.style.as_str()
+    }
+
+    /**
+     * Adds a file to write next to the output file, unless one with the same path was added.
+     */
+    pub fn add_asset(&mut self, asset: Asset) {
+        if !self
+            .assets
             .iter()
-            .fold(self.accumulator.to_string(), |acc, (regex, var_color)| {
-                regex
-                    .replace_all(
-                        acc.as_str(),
-                        format!("var({})", var_color.as_str()).as_str(),
-                    )
-                    .to_string()
-            })
-            .to_string();
+            .any(|existing| existing.path() == asset.path())
+        {
+            self.assets.push(asset);
+        }
+    }
 
-        self
+    /**
+     * The files taken from the document by the passes.
+     */
+    pub fn assets(&self) -> &[Asset] {
+        &self.assets
     }
 
-    pub fn accumulate(&'a self) -> &'a str {
-        self.accumulator.as_str()
+    pub fn accumulate(&'a self) -> &'a Document {
+        &self.document
     }
 }
 
-mod test {
-    use super::Modifier;
-    use crate::{
-        config::Config,
-        svg::{css_color::CssColor, parser::Parser, var_color::VarColor},
+/**
+ * The names and values of attributes, to compare them before and after a pass.
+ */
+pub(super) fn attribute_values(attributes: &[Attribute]) -> Vec<(String, String)> {
+    attributes
+        .iter()
+        .map(|attribute| (attribute.name().to_string(), attribute.value().to_string()))
+        .collect()
+}
+
+/**
+ * The number of attributes that were added, removed or given another value.
+ */
+pub(super) fn changed_attributes(before: &[(String, String)], after: &[(String, String)]) -> usize {
+    let changed = |from: &[(String, String)], to: &[(String, String)]| {
+        from.iter()
+            .filter(|(name, value)| {
+                to.iter()
+                    .find(|(other, _)| other == name)
+                    .is_none_or(|(_, other)| other != value)
+            })
+            .count()
     };
 
-    #[test]
-    pub fn test_modify_empty_tag() {
-        let input = r#"<!-- Soar!!! --><svg><!-- comment -->"#;
-        let (attributes, original_svg_string) = Parser::parse_attributes(input).unwrap();
+    changed(after, before)
+        + before
+            .iter()
+            .filter(|(name, _)| !after.iter().any(|(other, _)| other == name))
+            .count()
+}
 
-        let config = Config::default();
-        let mut modifier = Modifier::new(&config, input);
-        let result = modifier
-            .fix_svg_open_tag(original_svg_string.as_str(), attributes)
-            .accumulate();
+/**
+ * The variable a literal value of a token property is mapped to.
+ */
+pub(super) fn map_token(config: &Config, property: &str, value: &str) -> Option<String> {
+    config
+        .token_mappings()
+        .iter()
+        .find(|mapping| mapping.property().as_str() == property && mapping.value() == value.trim())
+        .map(|mapping| format!("var({})", mapping.var().as_str()))
+}
 
-        assert!(result.contains("Soar!!"));
-        assert!(result.contains("comment"));
-        assert!(result.contains("class='diagram'"));
-        assert!(result.contains("fill='none'"));
-        assert!(result.contains("xmlns='http://www.w3.org/2000/svg'"));
-        assert!(!result.contains("viewBox"));
-    }
+/**
+ * Applies the mappings to the declarations of a stylesheet, keeping its formatting.
+ * Stylesheet rules are not tied to one element, so color mappings limited to a selector are skipped.
+ */
+pub fn map_stylesheet(config: &Config, stylesheet: &Stylesheet) -> String {
+    let color_index = config.color_mappings().index();
 
-    #[test]
-    pub fn test_modify_tag_with_dimensions() {
-        let input = r#"<!-- whyyy --><svg width="69" height="420"><!-- :( -->"#;
-        let (attributes, original_svg_string) = Parser::parse_attributes(input).unwrap();
+    stylesheet.map_values(|declaration| {
+        let property = declaration.property();
 
-        let config = Config::default();
-        let mut modifier = Modifier::new(&config, input);
-        let result = modifier
-            .fix_svg_open_tag(original_svg_string.as_str(), attributes)
-            .accumulate();
+        if PAINT_ATTRIBUTES.contains(&property) {
+            return color_index.replace(declaration.value(), |mapping| {
+                mapping.applies_to_stylesheet(property)
+            });
+        }
 
-        assert!(result.contains("class='diagram'"));
-        assert!(result.contains("fill='none'"));
-        assert!(result.contains("xmlns='http://www.w3.org/2000/svg'"));
-        assert!(result.contains("viewBox='0 0 69 420'"));
-    }
+        map_token(config, property, declaration.value())
+    })
+}
+
+/**
+ * Rewrites the values of inline style declarations, keeping everything else as written.
+ * The callback receives the trimmed property name and the value, and returns a replacement value.
+ */
+pub(super) fn map_declarations(
+    declaration_regex: &Regex,
+    style: &str,
+    mut map: impl FnMut(&str, &str) -> Option<String>,
+) -> String {
+    declaration_regex
+        .replace_all(style, |declaration: &Captures| {
+            let value = map(declaration[1].trim(), &declaration[3])
+                .unwrap_or_else(|| declaration[3].to_string());
+
+            format!("{}{}{}", &declaration[1], &declaration[2], value)
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Modifier, PassConfig};
+    use crate::{config::Config, svg::document::Document};
 
     #[test]
-    pub fn test_map_colors() {
-        let input = "<svg fill='#ff00ff' stroke='red' fill='#ff00ff' stroke='blue'></svg>";
+    pub fn test_run_default_passes() {
+        let input = r#"<svg xmlns:inkscape="x" inkscape:version="1" width="10" height="10"><metadata/><path fill="red" stroke-width="2"/></svg>"#;
 
-        let mut config = Config::default();
+        let config = Config::default();
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+        let reports = modifier
+            .run()
+            .unwrap()
+            .iter()
+            .map(|report| report.to_string())
+            .collect::<Vec<_>>();
 
-        config.set_color_mapping(
-            CssColor::new("#ff00ff").unwrap(),
-            VarColor::new("--test-color").unwrap(),
+        assert_eq!(
+            reports,
+            vec![
+                "strip-metadata: elements removed: 1, attributes removed: 2",
+                "root-attributes: attributes changed: 4",
+                "accessibility: attributes changed: 1",
+                "map-colors: no changes",
+                "map-tokens: no changes",
+                "extract-styles: no changes",
+            ]
         );
-
-        config.set_color_mapping(
-            CssColor::new("red").unwrap(),
-            VarColor::new("--test-red").unwrap(),
+        assert_eq!(
+            modifier.accumulate().to_string(),
+            r#"<svg width="10" height="10" class='diagram' fill='none' xmlns='http://www.w3.org/2000/svg' viewBox='0 0 10 10' role='img'><path fill="red" stroke-width="2"/></svg>"#
         );
 
-        let mut modifier = Modifier::new(&config, input);
-        let result = modifier.map_colors().accumulate();
-        let expected = "<svg fill='var(--test-color)' stroke='var(--test-red)' fill='var(--test-color)' stroke='blue'></svg>";
+        let mut modifier = Modifier::new(&config, Document::parse("<svg/>").unwrap());
 
-        assert_eq!(result, expected);
+        assert!(modifier
+            .run()
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("root-attributes pass: "));
+    }
+
+    #[test]
+    pub fn test_pass_config() {
+        let passes: Vec<PassConfig> = serde_json::from_str(
+            r#"[
+                { "pass": "strip-metadata", "elements": [] },
+                { "pass": "extract-styles", "inline_styles": "classes" },
+                { "pass": "optimize", "precision": 2 },
+                { "pass": "extract-classes" },
+                { "pass": "extract-images", "static_path": "/diagram" }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            passes
+                .iter()
+                .map(|pass| pass.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "strip-metadata (namespaces sodipodi, inkscape; elements none)",
+                "extract-styles (inline styles classes)",
+                "optimize (precision 2)",
+                "extract-classes",
+                "extract-images (directory assets; static path /diagram)",
+            ]
+        );
+        assert!(serde_json::from_str::<Vec<PassConfig>>(r#"[{ "pass": "unknown" }]"#).is_err());
+        assert!(serde_json::from_str::<Vec<PassConfig>>(
+            r#"[{ "pass": "optimize", "precision": 20 }]"#
+        )
+        .is_err());
     }
 }
@@ -0,0 +1,636 @@
+use super::{
+    document::{Element, Node},
+    geometry::{BoundingBox, Transform},
+    modifier::{Modifier, Pass, PassReport},
+    path_data::{format_number, PathData},
+};
+use crate::css::inline_style::PRESENTATION_ATTRIBUTES;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+const MAX_PRECISION: u32 = 8;
+
+/**
+ * Attribute names and values, sorted by name.
+ */
+type AttributeList = Vec<(String, String)>;
+
+/**
+ * Pattern for a number in an attribute value, such as `-1.5`, `.5` or `1e-3`.
+ */
+const NUMBER_PATTERN: &str = r"-?(?:\d+\.?\d*|\.\d+)(?:[eE][-+]?\d+)?";
+
+/**
+ * Attributes holding numbers that are rounded to the configured precision.
+ */
+const NUMERIC_ATTRIBUTES: &[&str] = &[
+    "x",
+    "y",
+    "x1",
+    "y1",
+    "x2",
+    "y2",
+    "cx",
+    "cy",
+    "r",
+    "rx",
+    "ry",
+    "dx",
+    "dy",
+    "width",
+    "height",
+    "points",
+    "d",
+    "transform",
+    "stroke-width",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "font-size",
+    "letter-spacing",
+];
+
+/**
+ * Presentation attributes with their initial value, and whether the property is inherited.
+ */
+const DEFAULT_ATTRIBUTES: &[(&str, &str, bool)] = &[
+    ("opacity", "1", false),
+    ("fill-opacity", "1", true),
+    ("stroke-opacity", "1", true),
+    ("stop-opacity", "1", false),
+    ("stroke", "none", true),
+    ("stroke-width", "1", true),
+    ("stroke-linecap", "butt", true),
+    ("stroke-linejoin", "miter", true),
+    ("stroke-miterlimit", "4", true),
+    ("stroke-dasharray", "none", true),
+    ("stroke-dashoffset", "0", true),
+    ("fill-rule", "nonzero", true),
+    ("clip-rule", "nonzero", true),
+    ("visibility", "visible", true),
+    ("display", "inline", false),
+    ("font-style", "normal", true),
+    ("font-weight", "normal", true),
+    ("text-anchor", "start", true),
+    ("filter", "none", false),
+    ("clip-path", "none", false),
+    ("mask", "none", false),
+];
+
+/**
+ * Presentation attributes whose effect depends on the coordinate system of the element
+ * they are set on, so they are not moved from a group to its child.
+ */
+const LOCAL_ATTRIBUTES: &[&str] = &["clip-path", "mask", "filter"];
+
+/**
+ * Size optimizations of the document tree, each off by default, run by the `optimize` pass.
+ *
+ * - `remove_empty_containers` removes `<g>` and `<defs>` elements without content or id.
+ * - `collapse_groups` unwraps groups without attributes, and groups with one child that
+ *   only set presentation attributes the child does not set itself.
+ * - `remove_default_attributes` drops presentation attributes set to their initial value
+ *   when no ancestor sets a different value.
+ * - `precision` rounds numbers in geometry attributes to that many decimals.
+ * - `shorten_path_data` rewrites path data in its shortest form.
+ * - `merge_paths` merges adjacent paths with the same attributes whose bounds do not overlap.
+ */
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+#[serde(try_from = "UncheckedOptimizations")]
+pub struct Optimizations {
+    remove_empty_containers: bool,
+    collapse_groups: bool,
+    remove_default_attributes: bool,
+    precision: Option<u32>,
+    shorten_path_data: bool,
+    merge_paths: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct UncheckedOptimizations {
+    remove_empty_containers: bool,
+    collapse_groups: bool,
+    remove_default_attributes: bool,
+    precision: Option<u32>,
+    shorten_path_data: bool,
+    merge_paths: bool,
+}
+
+impl Optimizations {
+    /**
+     * Runs the enabled optimizations on the children of the root `<svg>`.
+     */
+    pub fn apply(&self, root: &mut Element) {
+        if self.remove_default_attributes {
+            remove_default_attributes(root);
+        }
+
+        if self.collapse_groups {
+            collapse_groups(root);
+        }
+
+        if self.remove_empty_containers {
+            remove_empty_containers(root);
+        }
+
+        if self.merge_paths {
+            merge_paths(root, self.precision);
+        }
+
+        if self.shorten_path_data {
+            shorten_path_data(root, self.precision);
+        }
+
+        if let Some(precision) = self.precision {
+            round_numbers(root, precision, self.shorten_path_data);
+        }
+    }
+}
+
+impl Pass for Optimizations {
+    fn name(&self) -> &'static str {
+        "optimize"
+    }
+
+    fn options(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+
+    fn run(&self, modifier: &mut Modifier) -> Result<PassReport> {
+        let document = modifier.document_mut();
+        let (elements, length) = (element_count(document.root()), document.to_string().len());
+
+        self.apply(document.root_mut());
+
+        let mut report = PassReport::new(self.name());
+
+        report.count(
+            "elements removed",
+            elements.saturating_sub(element_count(document.root())),
+        );
+        report.count(
+            "bytes saved",
+            length.saturating_sub(document.to_string().len()),
+        );
+
+        Ok(report)
+    }
+}
+
+impl TryFrom<UncheckedOptimizations> for Optimizations {
+    type Error = anyhow::Error;
+
+    fn try_from(unchecked: UncheckedOptimizations) -> Result<Self> {
+        if unchecked
+            .precision
+            .is_some_and(|precision| precision > MAX_PRECISION)
+        {
+            return Err(anyhow!(
+                "Precision must be at most {} decimals.",
+                MAX_PRECISION
+            ));
+        }
+
+        Ok(Self {
+            remove_empty_containers: unchecked.remove_empty_containers,
+            collapse_groups: unchecked.collapse_groups,
+            remove_default_attributes: unchecked.remove_default_attributes,
+            precision: unchecked.precision,
+            shorten_path_data: unchecked.shorten_path_data,
+            merge_paths: unchecked.merge_paths,
+        })
+    }
+}
+
+impl Display for Optimizations {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let precision = self
+            .precision
+            .map(|precision| format!("precision {}", precision));
+
+        let enabled = [
+            (self.remove_empty_containers, "remove empty containers"),
+            (self.collapse_groups, "collapse groups"),
+            (self.remove_default_attributes, "remove default attributes"),
+            (self.shorten_path_data, "shorten path data"),
+            (self.merge_paths, "merge paths"),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| name.to_string())
+        .chain(precision)
+        .collect::<Vec<_>>();
+
+        match enabled.is_empty() {
+            true => write!(f, "none"),
+            false => write!(f, "{}", enabled.join(", ")),
+        }
+    }
+}
+
+fn element_count(root: &Element) -> usize {
+    let mut count = 0;
+
+    root.walk(&mut |_, _| count += 1);
+    count
+}
+
+fn is_whitespace(node: &Node) -> bool {
+    matches!(node, Node::Text(text) if text.trim().is_empty())
+}
+
+fn has_expression(element: &Element) -> bool {
+    element
+        .attributes()
+        .iter()
+        .any(|attribute| attribute.is_expression() || attribute.value().contains(['{', '}']))
+}
+
+fn remove_default_attributes(root: &mut Element) {
+    let same_value =
+        |value: &str, default: &str| match (value.parse::<f64>(), default.parse::<f64>()) {
+            (Ok(value), Ok(default)) => value == default,
+            _ => value.trim().eq_ignore_ascii_case(default),
+        };
+
+    root.walk_mut(&mut |element, ancestors| {
+        if ancestors.is_empty() {
+            return;
+        }
+
+        element.retain_attributes(|attribute| {
+            let Some(&(_, default, inherited)) = DEFAULT_ATTRIBUTES
+                .iter()
+                .find(|(name, _, _)| *name == attribute.name())
+            else {
+                return true;
+            };
+
+            let inherits_default = !inherited
+                || ancestors
+                    .iter()
+                    .rev()
+                    .find_map(|ancestor| ancestor.property(attribute.name()))
+                    .is_none_or(|value| same_value(value, default));
+
+            attribute.is_expression()
+                || !same_value(attribute.value(), default)
+                || !inherits_default
+        });
+    });
+}
+
+fn collapse_groups(element: &mut Element) {
+    for child in element.children_mut().iter_mut() {
+        if let Node::Element(child) = child {
+            collapse_groups(child);
+        }
+    }
+
+    if element.name() == "switch" {
+        return;
+    }
+
+    let children = std::mem::take(element.children_mut());
+
+    for node in children {
+        let Node::Element(mut group) = node else {
+            element.children_mut().push(node);
+            continue;
+        };
+
+        if group.name() != "g" || has_expression(&group) {
+            element.children_mut().push(Node::Element(group));
+            continue;
+        }
+
+        if group.attributes().is_empty() {
+            element
+                .children_mut()
+                .extend(std::mem::take(group.children_mut()));
+            continue;
+        }
+
+        let content = group
+            .children()
+            .iter()
+            .filter(|node| !is_whitespace(node))
+            .collect::<Vec<_>>();
+
+        let movable = match content.as_slice() {
+            [Node::Element(child)] => group.attributes().iter().all(|attribute| {
+                PRESENTATION_ATTRIBUTES.contains(&attribute.name())
+                    && !LOCAL_ATTRIBUTES.contains(&attribute.name())
+                    && child.property(attribute.name()).is_none()
+            }),
+            _ => false,
+        };
+
+        if !movable {
+            element.children_mut().push(Node::Element(group));
+            continue;
+        }
+
+        let child = std::mem::take(group.children_mut())
+            .into_iter()
+            .find(|node| !is_whitespace(node));
+
+        if let Some(Node::Element(mut child)) = child {
+            for attribute in group.attributes() {
+                child.set_attribute(attribute.name(), attribute.value());
+            }
+
+            element.children_mut().push(Node::Element(child));
+        }
+    }
+}
+
+fn remove_empty_containers(element: &mut Element) {
+    for child in element.children_mut().iter_mut() {
+        if let Node::Element(child) = child {
+            remove_empty_containers(child);
+        }
+    }
+
+    element.retain_children(|node| {
+        !matches!(node, Node::Element(child)
+            if matches!(child.name(), "g" | "defs")
+                && child.attribute("id").is_none()
+                && child.children().iter().all(is_whitespace))
+    });
+}
+
+/**
+ * Merges runs of sibling paths that differ only in their path data. Paths with an id, markers
+ * or expressions are left alone, as are paths whose bounds, grown by the stroke width,
+ * overlap the paths merged so far, since overlapping fills or translucent strokes would change.
+ */
+fn merge_paths(element: &mut Element, precision: Option<u32>) {
+    struct Run {
+        index: usize,
+        attributes: AttributeList,
+        bounds: BoundingBox,
+        data: PathData,
+        merged: bool,
+    }
+
+    fn finish(children: &mut [Node], run: Option<Run>, precision: Option<u32>) {
+        if let Some(run) = run.filter(|run| run.merged) {
+            if let Node::Element(path) = &mut children[run.index] {
+                path.set_attribute("d", run.data.write(precision).as_str());
+            }
+        }
+    }
+
+    for child in element.children_mut().iter_mut() {
+        if let Node::Element(child) = child {
+            merge_paths(child, precision);
+        }
+    }
+
+    let mut children: Vec<Node> = Vec::new();
+    let mut run: Option<Run> = None;
+
+    for node in std::mem::take(element.children_mut()) {
+        if is_whitespace(&node) {
+            children.push(node);
+            continue;
+        }
+
+        let candidate = match &node {
+            Node::Element(path) => mergeable_path(path),
+            _ => None,
+        };
+
+        let Some((attributes, bounds, data)) = candidate else {
+            finish(&mut children, run.take(), precision);
+            children.push(node);
+            continue;
+        };
+
+        match run.as_mut() {
+            Some(current)
+                if current.attributes == attributes && !current.bounds.intersects(&bounds) =>
+            {
+                current.bounds = current.bounds.union(&bounds);
+                current.data.append(data);
+                current.merged = true;
+            }
+            _ => {
+                finish(&mut children, run.take(), precision);
+                run = Some(Run {
+                    index: children.len(),
+                    attributes,
+                    bounds,
+                    data,
+                    merged: false,
+                });
+                children.push(node);
+            }
+        }
+    }
+
+    finish(&mut children, run, precision);
+
+    *element.children_mut() = children;
+}
+
+/**
+ * The attributes other than `d`, sorted, the bounds grown by half the stroke width,
+ * and the parsed path data of a path that can be merged with others.
+ */
+fn mergeable_path(path: &Element) -> Option<(AttributeList, BoundingBox, PathData)> {
+    if path.name() != "path" || path.attribute("id").is_some() || has_expression(path) {
+        return None;
+    }
+
+    let mut attributes = path
+        .attributes()
+        .iter()
+        .filter(|attribute| attribute.name() != "d")
+        .map(|attribute| (attribute.name().to_string(), attribute.value().to_string()))
+        .collect::<Vec<_>>();
+
+    if attributes
+        .iter()
+        .any(|(name, _)| name.starts_with("marker"))
+        || path
+            .property("marker-start")
+            .or(path.property("marker-mid"))
+            .or(path.property("marker-end"))
+            .is_some()
+    {
+        return None;
+    }
+
+    attributes.sort();
+
+    let data = PathData::parse(path.attribute("d")?).ok()?;
+    let bounds = BoundingBox::of_shape(path, &Transform::default())?;
+    let margin = path
+        .property("stroke-width")
+        .and_then(|width| width.trim_end_matches("px").parse::<f64>().ok())
+        .unwrap_or(1.0)
+        / 2.0;
+
+    let bounds = BoundingBox::new(
+        bounds.min_x() - margin,
+        bounds.min_y() - margin,
+        bounds.min_x() + bounds.width() + margin,
+        bounds.min_y() + bounds.height() + margin,
+    );
+
+    Some((attributes, bounds, data))
+}
+
+fn shorten_path_data(root: &mut Element, precision: Option<u32>) {
+    root.walk_mut(&mut |element, _| {
+        if element.name() != "path" || has_expression(element) {
+            return;
+        }
+
+        let Some(data) = element
+            .attribute("d")
+            .and_then(|data| PathData::parse(data).ok())
+        else {
+            return;
+        };
+
+        element.set_attribute("d", data.write(precision).as_str());
+    });
+}
+
+/**
+ * Rounds the numbers in geometry attributes below the root. Path data is skipped when it
+ * was already written at this precision.
+ */
+fn round_numbers(root: &mut Element, precision: u32, skip_path_data: bool) {
+    let number_regex = Regex::new(NUMBER_PATTERN).expect("Failed to parse number pattern.");
+
+    root.walk_mut(&mut |element, ancestors| {
+        if ancestors.is_empty() {
+            return;
+        }
+
+        for attribute in element.attributes_mut() {
+            let name = attribute.name();
+
+            if attribute.is_expression()
+                || !NUMERIC_ATTRIBUTES.contains(&name)
+                || (skip_path_data && name == "d")
+            {
+                continue;
+            }
+
+            let rounded = number_regex
+                .replace_all(attribute.value(), |number: &regex::Captures| {
+                    number[0]
+                        .parse::<f64>()
+                        .map(|value| format_number(value, Some(precision)))
+                        .unwrap_or_else(|_| number[0].to_string())
+                })
+                .to_string();
+
+            attribute.set_value(rounded.as_str());
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::Optimizations;
+    use crate::{
+        config::Config,
+        svg::{
+            document::Document,
+            modifier::{Modifier, Pass},
+        },
+    };
+
+    fn optimized(input: &str, optimizations: &str) -> String {
+        let optimizations: Optimizations = serde_json::from_str(optimizations).unwrap();
+        let mut document = Document::parse(input).unwrap();
+
+        optimizations.apply(document.root_mut());
+        document.to_string()
+    }
+
+    #[test]
+    fn test_remove_default_attributes() {
+        assert_eq!(
+            optimized(
+                r#"<svg opacity="1"><path opacity="1.0" stroke-width="1" fill-rule="nonzero"/><g stroke-width="2"><path stroke-width="1" stroke="none"/></g></svg>"#,
+                r#"{"remove_default_attributes": true}"#
+            ),
+            r#"<svg opacity="1"><path/><g stroke-width="2"><path stroke-width="1"/></g></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_collapse_groups_and_remove_empty_containers() {
+        assert_eq!(
+            optimized(
+                r#"<svg><g><g fill="red"> <path d="M0 0"/> </g><g opacity=".5"><path opacity=".5"/></g><g clip-path="url(#c)"><path/></g></g><defs> </defs><g id="valve"/><g><g></g></g></svg>"#,
+                r#"{"collapse_groups": true, "remove_empty_containers": true}"#
+            ),
+            r#"<svg><path d="M0 0" fill='red'/><g opacity=".5"><path opacity=".5"/></g><g clip-path="url(#c)"><path/></g><g id="valve"/></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_report_savings() {
+        let config = Config::default();
+        let document = Document::parse(r#"<svg><g><path opacity="1"/></g><g/></svg>"#).unwrap();
+        let mut modifier = Modifier::new(&config, document);
+        let optimizations: Optimizations = serde_json::from_str(
+            r#"{"remove_default_attributes": true, "collapse_groups": true, "remove_empty_containers": true}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            optimizations.run(&mut modifier).unwrap().to_string(),
+            "optimize: elements removed: 2, bytes saved: 23"
+        );
+        assert_eq!(modifier.accumulate().to_string(), "<svg><path/></svg>");
+    }
+
+    #[test]
+    fn test_merge_paths() {
+        assert_eq!(
+            optimized(
+                r#"<svg><path fill="red" d="M0 0 H10 V10 Z"/>
+<path fill="red" d="m20 0 h10 v10 z"/><path fill="red" d="M25 5 h10"/><path fill="blue" d="M50 0 h1"/><path id="a" fill="blue" d="M60 0 h1"/></svg>"#,
+                r#"{"merge_paths": true}"#
+            ),
+            r#"<svg><path fill="red" d="m0 0h10v10zm20 0h10v10z"/>
+<path fill="red" d="M25 5 h10"/><path fill="blue" d="M50 0 h1"/><path id="a" fill="blue" d="M60 0 h1"/></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_round_numbers_and_shorten_path_data() {
+        assert_eq!(
+            optimized(
+                r#"<svg width="10.123456"><rect x="0.123456" y="1e-5" transform="translate(10.5555 -3.33333)"/><path d="M 0.12345 0 L 10.12345 0"/></svg>"#,
+                r#"{"precision": 2}"#
+            ),
+            r#"<svg width="10.123456"><rect x=".12" y="0" transform="translate(10.56 -3.33)"/><path d="M .12 0 L 10.12 0"/></svg>"#
+        );
+        assert_eq!(
+            optimized(
+                r#"<svg><path d="M 0.12345 0 L 10.12345 0"/></svg>"#,
+                r#"{"precision": 2, "shorten_path_data": true}"#
+            ),
+            r#"<svg><path d="m.12 0h10"/></svg>"#
+        );
+        assert!(serde_json::from_str::<Optimizations>(r#"{"precision": 20}"#).is_err());
+        assert_eq!(
+            serde_json::from_str::<Optimizations>(r#"{"merge_paths": true, "precision": 3}"#)
+                .unwrap()
+                .to_string(),
+            "merge paths, precision 3"
+        );
+    }
+}
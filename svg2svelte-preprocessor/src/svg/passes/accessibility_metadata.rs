@@ -0,0 +1,160 @@
+use crate::svg::{
+    accessibility::{escape_text, layer_label},
+    document::{Element, Node},
+    modifier::{attribute_values, changed_attributes, Modifier, Pass, PassReport},
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/**
+ * Ids of the generated `<title>` and `<desc>` the root `<svg>` is labelled and described by.
+ */
+const TITLE_ID: &str = "diagram-title";
+const DESCRIPTION_ID: &str = "diagram-description";
+
+/**
+ * Applies the accessibility metadata of the config: the role, title and description of the
+ * root `<svg>`, and labels for the layers whose id matches a labelled id pattern.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct AccessibilityMetadata {}
+
+impl Pass for AccessibilityMetadata {
+    fn name(&self) -> &'static str {
+        "accessibility"
+    }
+
+    fn run(&self, modifier: &mut Modifier) -> Result<PassReport> {
+        let config = modifier.config();
+        let accessibility = config.accessibility();
+        let root = modifier.document_mut().root_mut();
+        let before = attribute_values(root.attributes());
+
+        root.set_attribute(
+            "role",
+            accessibility
+                .role(!config.component_kinds().is_empty())
+                .as_str(),
+        );
+
+        let metadata = [
+            ("title", TITLE_ID, "aria-labelledby", accessibility.title()),
+            (
+                "desc",
+                DESCRIPTION_ID,
+                "aria-describedby",
+                accessibility.description(),
+            ),
+        ];
+
+        let mut children = Vec::new();
+
+        for (name, id, reference, text) in metadata {
+            let Some(text) = text else {
+                continue;
+            };
+
+            root.retain_children(
+                |child| !matches!(child, Node::Element(element) if element.name() == name),
+            );
+
+            let mut element = Element::new(name);
+            element.set_attribute("id", id);
+            element.children_mut().push(Node::Text(escape_text(text)));
+
+            root.set_attribute(reference, id);
+            children.push(Node::Element(element));
+        }
+
+        let mut report = PassReport::new(self.name());
+
+        report.count(
+            "attributes changed",
+            changed_attributes(&before, &attribute_values(root.attributes())),
+        );
+        report.count("metadata elements", children.len());
+
+        root.children_mut().splice(0..0, children);
+
+        let mut labelled = 0;
+
+        root.walk_mut(&mut |element, ancestors| {
+            let Some(id) = element.attribute("id") else {
+                return;
+            };
+
+            if ancestors.is_empty()
+                || !accessibility.is_labelled(id)
+                || element.attribute("aria-label").is_some()
+            {
+                return;
+            }
+
+            let label = layer_label(id);
+            element.set_attribute("aria-label", label.as_str());
+
+            if element.attribute("role").is_none() {
+                let role = match element.name() {
+                    "g" | "svg" | "a" | "switch" => "group",
+                    _ => "img",
+                };
+
+                element.set_attribute("role", role);
+            }
+
+            labelled += 1;
+        });
+
+        report.count("elements labelled", labelled);
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AccessibilityMetadata;
+    use crate::{
+        config::Config,
+        svg::{
+            accessibility::Accessibility,
+            document::Document,
+            modifier::{Modifier, Pass},
+        },
+    };
+
+    #[test]
+    pub fn test_accessibility_metadata() {
+        let input = r#"<svg width="10" height="10"><title>Old</title><g id="oxidizer-tank"><path id="valve-1" aria-label="Main valve"/><path id="pipe-2"/></g></svg>"#;
+
+        let mut config = Config::default();
+        config.set_accessibility(
+            Accessibility::new(
+                Some("Propulsion <P&ID>".to_string()),
+                Some("Live valve states".to_string()),
+                None,
+                vec!["oxidizer-*".to_string(), "valve-*".to_string()],
+            )
+            .unwrap(),
+        );
+
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+        let report = AccessibilityMetadata {}.run(&mut modifier).unwrap();
+        let result = modifier.accumulate().to_string();
+
+        assert!(result.contains(
+            "role='group' aria-labelledby='diagram-title' aria-describedby='diagram-description'>"
+        ));
+        assert!(result.contains("><title id='diagram-title'>Propulsion &lt;P&amp;ID&gt;</title><desc id='diagram-description'>Live valve states</desc><g"));
+        assert!(!result.contains("Old"));
+        assert!(
+            result.contains(r#"<g id="oxidizer-tank" aria-label='Oxidizer tank' role='group'>"#)
+        );
+        assert!(result.contains(r#"<path id="valve-1" aria-label="Main valve"/>"#));
+        assert!(result.contains(r#"<path id="pipe-2"/>"#));
+        assert_eq!(
+            report.to_string(),
+            "accessibility: attributes changed: 3, metadata elements: 2, elements labelled: 1"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use crate::{
+    css::inline_style::PRESENTATION_ATTRIBUTES,
+    svg::{
+        document::{decode_entities, Element},
+        modifier::{Modifier, Pass, PassReport},
+    },
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/**
+ * Prefix of the classes generated for repeated presentation attributes.
+ */
+const ATTRIBUTE_CLASS_PREFIX: &str = "attrs-";
+
+/**
+ * Presentation attributes that take a unitless length, which CSS only accepts with a unit.
+ */
+const PIXEL_ATTRIBUTES: &[&str] = &["font-size", "letter-spacing"];
+
+/**
+ * Moves combinations of presentation attributes that repeat on several elements into
+ * generated classes, when that shortens the markup. Class names are derived from the
+ * declarations, so a combination keeps its class when the rest of the diagram changes.
+ * The rules select through `:where`, so like the attributes they replace they have no
+ * specificity and any author rule still overrides them.
+ * Values referencing ids or holding braces stay on the elements, since ids are scoped
+ * per instance and braces may be placeholders.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct ExtractClasses {}
+
+impl Pass for ExtractClasses {
+    fn name(&self) -> &'static str {
+        "extract-classes"
+    }
+
+    fn run(&self, modifier: &mut Modifier) -> Result<PassReport> {
+        let document = modifier.document_mut();
+
+        let mut combinations: Vec<(Vec<(String, String)>, usize)> = Vec::new();
+        let mut indices: HashMap<Vec<(String, String)>, usize> = HashMap::new();
+
+        document.root().walk(&mut |element, ancestors| {
+            let Some(combination) = attribute_combination(element, ancestors) else {
+                return;
+            };
+
+            match indices.get(&combination) {
+                Some(&index) => combinations[index].1 += 1,
+                None => {
+                    indices.insert(combination.clone(), combinations.len());
+                    combinations.push((combination, 1));
+                }
+            }
+        });
+
+        let mut taken = HashSet::new();
+        let mut classes: HashMap<Vec<(String, String)>, String> = HashMap::new();
+        let mut rules = Vec::new();
+
+        for (combination, count) in combinations {
+            let name = class_name(&combination, &taken);
+            let rule = format!(
+                ":where(.{}) {{\n{}}}",
+                name,
+                combination
+                    .iter()
+                    .map(|(name, value)| format!("\t{}: {};\n", name, css_value(name, value)))
+                    .collect::<String>()
+            );
+
+            let attributes_length = combination
+                .iter()
+                .map(|(name, value)| name.len() + value.len() + 4)
+                .sum::<usize>();
+            let class_length = name.len() + " class=''".len();
+
+            if count < 2 || count * attributes_length <= count * class_length + rule.len() {
+                continue;
+            }
+
+            taken.insert(name.clone());
+            classes.insert(combination, name);
+            rules.push(rule);
+        }
+
+        let mut classed = 0;
+
+        document.root_mut().walk_mut(&mut |element, ancestors| {
+            let Some(name) = attribute_combination(element, ancestors)
+                .and_then(|combination| classes.get(&combination))
+            else {
+                return;
+            };
+
+            element.retain_attributes(|attribute| {
+                attribute.is_expression() || !PRESENTATION_ATTRIBUTES.contains(&attribute.name())
+            });
+
+            let class = match element.attribute("class") {
+                Some(class) => format!("{} {}", class, name),
+                None => name.clone(),
+            };
+
+            element.set_attribute("class", class.as_str());
+            classed += 1;
+        });
+
+        let mut report = PassReport::new(self.name());
+
+        report.count("classes generated", rules.len());
+        report.count("elements classed", classed);
+
+        modifier.append_style(rules.join("\n\n").as_str());
+
+        Ok(report)
+    }
+}
+
+/**
+ * The decoded presentation attributes of an element sorted by name, or `None` when it
+ * has none or any of them cannot be moved to a class. The root is left alone, as it takes its
+ * attributes from the root rules.
+ */
+fn attribute_combination(
+    element: &Element,
+    ancestors: &[&Element],
+) -> Option<Vec<(String, String)>> {
+    if ancestors.is_empty() {
+        return None;
+    }
+
+    let mut combination = Vec::new();
+
+    for attribute in element.attributes() {
+        if !PRESENTATION_ATTRIBUTES.contains(&attribute.name()) {
+            continue;
+        }
+
+        if attribute.is_expression() {
+            return None;
+        }
+
+        let value = decode_entities(attribute.value());
+
+        if value.contains(['{', '}']) || value.contains("url(") {
+            return None;
+        }
+
+        combination.push((attribute.name().to_string(), value.trim().to_string()));
+    }
+
+    combination.sort();
+    (!combination.is_empty()).then_some(combination)
+}
+
+/**
+ * A presentation attribute value written as a CSS value.
+ */
+fn css_value(name: &str, value: &str) -> String {
+    match PIXEL_ATTRIBUTES.contains(&name) && value.parse::<f64>().is_ok() {
+        true => format!("{}px", value),
+        false => value.to_string(),
+    }
+}
+
+/**
+ * A class name from an FNV-1a hash of the declarations, lengthened when it is already taken.
+ */
+fn class_name(combination: &[(String, String)], taken: &HashSet<String>) -> String {
+    let hash = combination
+        .iter()
+        .flat_map(|(name, value)| [name.as_bytes(), b":", value.as_bytes(), b";"])
+        .flatten()
+        .fold(0xcbf29ce484222325u64, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    let hex = format!("{:016x}", hash);
+
+    (6..=hex.len())
+        .map(|length| format!("{}{}", ATTRIBUTE_CLASS_PREFIX, &hex[..length]))
+        .find(|name| !taken.contains(name))
+        .unwrap_or_else(|| format!("{}{}-{}", ATTRIBUTE_CLASS_PREFIX, hex, taken.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::ExtractClasses;
+    use crate::{
+        config::Config,
+        svg::{
+            document::Document,
+            modifier::{Modifier, Pass},
+            passes::extract_styles::ExtractStyles,
+        },
+    };
+
+    #[test]
+    pub fn test_extract_classes() {
+        let input = r#"<svg fill="none"><g><path stroke-width="2" fill="var(--pipe)" stroke="var(--outline)"/><path class="a" fill="var(--pipe)" stroke="var(--outline)" stroke-width="2"/><path fill="var(--pipe)" stroke="var(--outline)" stroke-width="2" clip-path="url(#c)"/></g><path stroke="var(--outline)" fill="var(--pipe)" stroke-width="2"/><text font-size="12" fill="var(--label)"/><text font-size="12" fill="var(--label)"/><text font-size="12" fill="var(--label)"/><text font-size="12" fill="var(--label)"/><text font-size="12" fill="var(--label)"/><rect fill="red"/><rect fill="red"/></svg>"#;
+
+        let config = Config::default();
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+        let report = ExtractClasses {}.run(&mut modifier).unwrap();
+
+        assert_eq!(
+            modifier.accumulate().to_string(),
+            r#"<svg fill="none"><g><path class='attrs-75d59e'/><path class="a attrs-75d59e"/><path fill="var(--pipe)" stroke="var(--outline)" stroke-width="2" clip-path="url(#c)"/></g><path class='attrs-75d59e'/><text class='attrs-448c2b'/><text class='attrs-448c2b'/><text class='attrs-448c2b'/><text class='attrs-448c2b'/><text class='attrs-448c2b'/><rect fill="red"/><rect fill="red"/></svg>"#
+        );
+        assert_eq!(
+            modifier.extracted_style(),
+            ":where(.attrs-75d59e) {\n\tfill: var(--pipe);\n\tstroke: var(--outline);\n\tstroke-width: 2;\n}\n\n:where(.attrs-448c2b) {\n\tfill: var(--label);\n\tfont-size: 12px;\n}"
+        );
+        assert_eq!(
+            report.to_string(),
+            "extract-classes: classes generated: 2, elements classed: 8"
+        );
+    }
+
+    #[test]
+    pub fn test_extracted_values_are_decoded() {
+        let input = r#"<svg><text font-family="&quot;Segoe UI&quot;, sans-serif" fill="&#x23;000"/><text font-family="&quot;Segoe UI&quot;, sans-serif" fill="&#x23;000"/><text font-family="&quot;Segoe UI&quot;, sans-serif" fill="&#x23;000"/><text font-family="&quot;Segoe UI&quot;, sans-serif" fill="&#x23;000"/><text fill="&#123;pt1&#125;"/><text fill="&#123;pt1&#125;"/><text fill="&#123;pt1&#125;"/><text fill="&#123;pt1&#125;"/></svg>"#;
+
+        let config = Config::default();
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+        let report = ExtractClasses {}.run(&mut modifier).unwrap();
+
+        assert!(modifier
+            .extracted_style()
+            .contains("\tfill: #000;\n\tfont-family: \"Segoe UI\", sans-serif;\n}"));
+        assert_eq!(
+            report.to_string(),
+            "extract-classes: classes generated: 1, elements classed: 4"
+        );
+    }
+
+    #[test]
+    pub fn test_author_rules_override_extracted_classes() {
+        let input = r#"<svg><style>path { stroke: red; } .cls-1 { fill: blue; }</style><path class="cls-1" fill="var(--pipe)" stroke="var(--outline)"/><path fill="var(--pipe)" stroke="var(--outline)"/><path fill="var(--pipe)" stroke="var(--outline)"/><path fill="var(--pipe)" stroke="var(--outline)"/></svg>"#;
+
+        let config = Config::default();
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+
+        ExtractStyles::default().run(&mut modifier).unwrap();
+        ExtractClasses {}.run(&mut modifier).unwrap();
+
+        assert_eq!(
+            modifier.extracted_style(),
+            "path { stroke: red; } .cls-1 { fill: blue; }\n\n:where(.attrs-a85e04) {\n\tfill: var(--pipe);\n\tstroke: var(--outline);\n}"
+        );
+        assert_eq!(
+            modifier.accumulate().to_string(),
+            r#"<svg><path class="cls-1 attrs-a85e04"/><path class='attrs-a85e04'/><path class='attrs-a85e04'/><path class='attrs-a85e04'/></svg>"#
+        );
+    }
+}
@@ -0,0 +1,346 @@
+use crate::svg::{
+    document::decode_entities,
+    modifier::{Modifier, Pass, PassReport},
+};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/**
+ * Number of hex digits of the content hash used in asset file names and import bindings.
+ */
+const HASH_LENGTH: usize = 16;
+
+/**
+ * Image types that are extracted, with the extension of their files.
+ */
+const IMAGE_EXTENSIONS: [(&str, &str); 8] = [
+    ("image/png", "png"),
+    ("image/jpeg", "jpg"),
+    ("image/jpg", "jpg"),
+    ("image/gif", "gif"),
+    ("image/webp", "webp"),
+    ("image/avif", "avif"),
+    ("image/bmp", "bmp"),
+    ("image/svg+xml", "svg"),
+];
+
+/**
+ * A file taken out of the document, written next to the output file.
+ */
+pub struct Asset {
+    /**
+     * Path of the file relative to the output file, with `/` separators.
+     */
+    path: String,
+    data: Vec<u8>,
+    /**
+     * The variable the file is imported as, unless it is referenced by a static path.
+     */
+    binding: Option<String>,
+}
+
+impl Asset {
+    #[inline(always)]
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    #[inline(always)]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    #[inline(always)]
+    pub fn binding(&self) -> Option<&str> {
+        self.binding.as_deref()
+    }
+
+    /**
+     * The import of the file, relative to the component.
+     */
+    pub fn import_path(&self) -> String {
+        match self.path.starts_with("../") {
+            true => self.path.clone(),
+            false => format!("./{}", self.path),
+        }
+    }
+}
+
+/**
+ * Moves base64 `data:` images referenced by `href` and `xlink:href` into asset files named
+ * after a hash of their content, so identical images share one file. The references become
+ * imports of the files, or static paths when a static path is configured.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct ExtractImages {
+    /**
+     * Directory the files are written to, relative to the output file.
+     */
+    #[serde(default = "ExtractImages::default_directory")]
+    directory: String,
+    /**
+     * URL path the files are served under, such as `/diagram`. When set, references
+     * are written as static paths instead of imports.
+     */
+    #[serde(default)]
+    static_path: Option<String>,
+}
+
+impl Default for ExtractImages {
+    fn default() -> Self {
+        Self {
+            directory: Self::default_directory(),
+            static_path: None,
+        }
+    }
+}
+
+impl ExtractImages {
+    fn default_directory() -> String {
+        "assets".to_string()
+    }
+
+    /**
+     * The file for the image of a data URI, or `None` when the URI is not a base64 image.
+     */
+    fn asset(&self, uri: &str) -> Option<Result<Asset>> {
+        let (metadata, data) = uri.trim().strip_prefix("data:")?.split_once(',')?;
+        let mut parameters = metadata.split(';');
+        let mime = parameters.next()?.trim().to_ascii_lowercase();
+
+        if !parameters.any(|parameter| parameter.trim().eq_ignore_ascii_case("base64")) {
+            return None;
+        }
+
+        let extension = IMAGE_EXTENSIONS
+            .iter()
+            .find(|(image_mime, _)| *image_mime == mime)
+            .map(|(_, extension)| extension)?;
+
+        let data = decode_entities(data)
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect::<String>();
+
+        let data = match STANDARD.decode(data) {
+            Ok(data) => data,
+            Err(error) => return Some(Err(anyhow!("Invalid {} data: {}", mime, error))),
+        };
+
+        let hash = Sha256::digest(&data)[..HASH_LENGTH / 2]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+
+        let directory = self.directory.trim_end_matches('/');
+        let file_name = format!("{}.{}", hash, extension);
+
+        let path = match directory.is_empty() {
+            true => file_name,
+            false => format!("{}/{}", directory, file_name),
+        };
+
+        let binding = self.static_path.is_none().then(|| format!("image{}", hash));
+
+        Some(Ok(Asset {
+            path,
+            data,
+            binding,
+        }))
+    }
+
+    /**
+     * The `href` value or expression that references an asset.
+     */
+    fn reference(&self, asset: &Asset) -> String {
+        match asset.binding() {
+            Some(binding) => binding.to_string(),
+            None => {
+                let static_path = self.static_path.as_deref().unwrap_or_default();
+                let file_name = asset.path().rsplit('/').next().unwrap_or(asset.path());
+
+                format!("{}/{}", static_path.trim_end_matches('/'), file_name)
+            }
+        }
+    }
+}
+
+impl Pass for ExtractImages {
+    fn name(&self) -> &'static str {
+        "extract-images"
+    }
+
+    fn options(&self) -> Option<String> {
+        match &self.static_path {
+            Some(static_path) => Some(format!(
+                "directory {}; static path {}",
+                self.directory, static_path
+            )),
+            None => Some(format!("directory {}; imports", self.directory)),
+        }
+    }
+
+    fn run(&self, modifier: &mut Modifier) -> Result<PassReport> {
+        let mut assets: Vec<Asset> = Vec::new();
+        let mut indices = HashMap::new();
+        let mut error = None;
+        let mut extracted = 0;
+
+        modifier
+            .document_mut()
+            .root_mut()
+            .walk_mut(&mut |element, _| {
+                let references = element
+                    .attributes()
+                    .iter()
+                    .filter(|attribute| {
+                        !attribute.is_expression()
+                            && matches!(attribute.name(), "href" | "xlink:href")
+                    })
+                    .filter_map(|attribute| {
+                        self.asset(attribute.value())
+                            .map(|asset| (attribute.name().to_string(), asset))
+                    })
+                    .collect::<Vec<_>>();
+
+                for (name, asset) in references {
+                    let asset = match asset {
+                        Ok(asset) => asset,
+                        Err(asset_error) => {
+                            let id = element.attribute("id").unwrap_or(element.name());
+                            error.get_or_insert(anyhow!("Image {}: {}", id, asset_error));
+                            continue;
+                        }
+                    };
+
+                    let index = *indices.entry(asset.path.clone()).or_insert_with(|| {
+                        assets.push(asset);
+                        assets.len() - 1
+                    });
+
+                    let asset = &assets[index];
+                    let reference = self.reference(asset);
+
+                    match asset.binding() {
+                        Some(_) => element.set_expression_attribute(name.as_str(), &reference),
+                        None => element.set_attribute(name.as_str(), &reference),
+                    }
+
+                    extracted += 1;
+                }
+            });
+
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        let mut report = PassReport::new(self.name());
+
+        report.count("images extracted", extracted);
+        report.count("duplicates", extracted - assets.len());
+
+        for asset in assets {
+            modifier.add_asset(asset);
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ExtractImages;
+    use crate::{
+        config::Config,
+        svg::{
+            document::Document,
+            modifier::{Modifier, Pass},
+        },
+    };
+
+    const PIXEL: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
+
+    #[test]
+    pub fn test_extract_images_as_imports() {
+        let input = format!(
+            r#"<svg><image id="logo" href="data:image/png;base64,{}"/><image xlink:href="data:image/png;base64,{}
+"/><use href="data:image/png;base64,{}"/><image href="logo.png"/><image href="data:text/plain;base64,aGk="/></svg>"#,
+            PIXEL,
+            PIXEL.replace("AAAA", "AAAA\n  "),
+            PIXEL.replace("AAAA", "AAAA&#10;"),
+        );
+
+        let config = Config::default();
+        let mut modifier = Modifier::new(&config, Document::parse(input.as_str()).unwrap());
+        let report = ExtractImages::default().run(&mut modifier).unwrap();
+
+        assert_eq!(
+            report.to_string(),
+            "extract-images: images extracted: 3, duplicates: 2"
+        );
+
+        let assets = modifier.assets();
+
+        assert_eq!(assets.len(), 1);
+        assert!(assets[0].path().starts_with("assets/"));
+        assert!(assets[0].path().ends_with(".png"));
+        assert_eq!(assets[0].path().len(), "assets/".len() + 16 + ".png".len());
+        assert!(assets[0].data().starts_with(b"\x89PNG"));
+
+        let binding = assets[0].binding().unwrap().to_string();
+
+        assert_eq!(
+            binding,
+            format!("image{}", &assets[0].path()["assets/".len()..][..16])
+        );
+        assert_eq!(
+            modifier.accumulate().to_string(),
+            format!(
+                r#"<svg><image id="logo" href={{{0}}}/><image xlink:href={{{0}}}/><use href={{{0}}}/><image href="logo.png"/><image href="data:text/plain;base64,aGk="/></svg>"#,
+                binding
+            )
+        );
+    }
+
+    #[test]
+    pub fn test_extract_images_as_static_paths() {
+        let input = format!(
+            r#"<svg><image href="data:image/png;base64,{}"/></svg>"#,
+            PIXEL
+        );
+
+        let pass: ExtractImages = serde_json::from_str(
+            r#"{ "directory": "../../static/diagram/", "static_path": "/diagram/" }"#,
+        )
+        .unwrap();
+
+        let config = Config::default();
+        let mut modifier = Modifier::new(&config, Document::parse(input.as_str()).unwrap());
+
+        pass.run(&mut modifier).unwrap();
+
+        let asset = &modifier.assets()[0];
+        let file_name = asset.path().strip_prefix("../../static/diagram/").unwrap();
+
+        assert!(asset.binding().is_none());
+        assert_eq!(
+            modifier.accumulate().to_string(),
+            format!(r#"<svg><image href="/diagram/{}"/></svg>"#, file_name)
+        );
+
+        let document =
+            Document::parse(r#"<svg><image id="logo" href="data:image/png;base64,@@"/></svg>"#)
+                .unwrap();
+        let mut modifier = Modifier::new(&config, document);
+
+        assert!(ExtractImages::default()
+            .run(&mut modifier)
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("Image logo: Invalid image/png data: "));
+    }
+}
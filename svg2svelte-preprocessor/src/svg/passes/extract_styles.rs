@@ -0,0 +1,309 @@
+use crate::{
+    css::{
+        inline_style::{InlineStyles, PRESENTATION_ATTRIBUTES},
+        stylesheet::{Declaration, Stylesheet},
+    },
+    svg::{
+        document::{decode_entities, Node},
+        modifier::{map_stylesheet, Modifier, Pass, PassReport},
+    },
+};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/**
+ * Prefix of the classes generated for inline styles.
+ */
+const INLINE_STYLE_CLASS_PREFIX: &str = "inline-style-";
+
+/**
+ * Moves the CSS of `<style>` elements into the component style, with color mappings that
+ * are not limited to a selector and token mappings applied, and handles inline `style`
+ * attributes as configured. Runs after the mappings, so inline styles are already mapped.
+ */
+#[derive(Serialize, Deserialize, Default)]
+pub struct ExtractStyles {
+    #[serde(default)]
+    inline_styles: InlineStyles,
+}
+
+impl ExtractStyles {
+    pub fn new(inline_styles: InlineStyles) -> Self {
+        Self { inline_styles }
+    }
+}
+
+impl Pass for ExtractStyles {
+    fn name(&self) -> &'static str {
+        "extract-styles"
+    }
+
+    fn options(&self) -> Option<String> {
+        Some(format!("inline styles {}", self.inline_styles))
+    }
+
+    fn run(&self, modifier: &mut Modifier) -> Result<PassReport> {
+        let config = modifier.config();
+        let document = modifier.document_mut();
+        let mut sources = Vec::new();
+
+        document.root().walk(&mut |element, _| {
+            if element.name() != "style" {
+                return;
+            }
+
+            let css = element
+                .children()
+                .iter()
+                .map(|node| match node {
+                    Node::Text(text) => decode_entities(text),
+                    Node::CData(data) => data.to_string(),
+                    _ => String::new(),
+                })
+                .collect::<String>();
+
+            sources.push(css);
+        });
+
+        document.root_mut().walk_mut(&mut |element, _| {
+            element.retain_children(
+                |child| !matches!(child, Node::Element(child) if child.name() == "style"),
+            );
+        });
+
+        let mut styles = Vec::new();
+
+        for source in sources.iter() {
+            let stylesheet = Stylesheet::parse(source.as_str())
+                .map_err(|error| anyhow!("Embedded style: {}", error))?;
+            let mapped = map_stylesheet(config, &stylesheet);
+
+            if !mapped.trim().is_empty() {
+                styles.push(mapped.trim().to_string());
+            }
+        }
+
+        let mut classes: Vec<(String, Vec<(String, String)>)> = Vec::new();
+        let mut error = None;
+        let mut inline_styles = 0;
+
+        if self.inline_styles != InlineStyles::Keep {
+            document.root_mut().walk_mut(&mut |element, _| {
+                let Some(style) = element.attribute("style") else {
+                    return;
+                };
+
+                let declarations = match Stylesheet::parse_inline(style) {
+                    Ok(declarations) => declarations,
+                    Err(parse_error) => {
+                        error.get_or_insert(anyhow!("Inline style {}: {}", style, parse_error));
+                        return;
+                    }
+                };
+
+                let remaining = match self.inline_styles {
+                    InlineStyles::Attributes => {
+                        let (attributes, remaining): (Vec<_>, Vec<_>) =
+                            declarations.iter().partition(|declaration| {
+                                PRESENTATION_ATTRIBUTES.contains(&declaration.property())
+                                    && !declaration.value().contains('!')
+                            });
+
+                        for declaration in attributes {
+                            element.set_attribute(declaration.property(), declaration.value());
+                        }
+
+                        remaining
+                    }
+                    _ => {
+                        let declarations = declarations
+                            .iter()
+                            .map(|declaration| {
+                                (
+                                    declaration.property().to_string(),
+                                    declaration.value().to_string(),
+                                )
+                            })
+                            .collect::<Vec<_>>();
+
+                        let index = match classes
+                            .iter()
+                            .position(|(_, existing)| *existing == declarations)
+                        {
+                            Some(index) => index,
+                            None => {
+                                let name =
+                                    format!("{}{}", INLINE_STYLE_CLASS_PREFIX, classes.len() + 1);
+                                classes.push((name, declarations));
+                                classes.len() - 1
+                            }
+                        };
+
+                        let class = match element.attribute("class") {
+                            Some(class) => format!("{} {}", class, classes[index].0),
+                            None => classes[index].0.clone(),
+                        };
+
+                        element.set_attribute("class", class.as_str());
+                        Vec::new()
+                    }
+                };
+
+                if remaining.len() < declarations.len() {
+                    inline_styles += 1;
+                }
+
+                let remaining = inline_declarations(&remaining);
+
+                match remaining.is_empty() {
+                    true => element.retain_attributes(|attribute| attribute.name() != "style"),
+                    false => element.set_attribute("style", remaining.as_str()),
+                }
+            });
+        }
+
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        let mut report = PassReport::new(self.name());
+
+        report.count("style elements extracted", sources.len());
+        report.count("inline styles moved", inline_styles);
+        report.count("classes generated", classes.len());
+
+        for (name, declarations) in classes {
+            let body = declarations
+                .iter()
+                .map(|(property, value)| format!("\t{}: {};\n", property, value))
+                .collect::<String>();
+
+            styles.push(format!(".{} {{\n{}}}", name, body));
+        }
+
+        modifier.append_style(styles.join("\n\n").as_str());
+
+        Ok(report)
+    }
+}
+
+/**
+ * Writes declarations back as the value of a `style` attribute.
+ */
+fn inline_declarations(declarations: &[&Declaration]) -> String {
+    declarations
+        .iter()
+        .map(|declaration| format!("{}: {}", declaration.property(), declaration.value()))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::ExtractStyles;
+    use crate::{
+        config::Config,
+        css::inline_style::InlineStyles,
+        svg::{
+            color_mapping::ColorMapping,
+            css_color::CssColor,
+            document::Document,
+            modifier::{Modifier, Pass},
+            passes::map_colors::MapColors,
+            selector::Selector,
+            var_color::VarColor,
+        },
+    };
+
+    fn extract(input: &str, inline_styles: InlineStyles) -> (String, String, String) {
+        let mut config = Config::default();
+
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("#ff0000").unwrap(),
+            VarColor::new("--alarm").unwrap(),
+        ));
+        config.set_color_mapping(
+            ColorMapping::new(
+                CssColor::new("#00ff00").unwrap(),
+                VarColor::new("--label").unwrap(),
+            )
+            .with_selector(Selector::new("text").unwrap()),
+        );
+
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+
+        MapColors {}.run(&mut modifier).unwrap();
+
+        let report = ExtractStyles::new(inline_styles)
+            .run(&mut modifier)
+            .unwrap();
+
+        (
+            modifier.accumulate().to_string(),
+            modifier.extracted_style().to_string(),
+            report.to_string(),
+        )
+    }
+
+    #[test]
+    pub fn test_extract_embedded_styles() {
+        let input = r##"<svg><defs><style>.cls-1 { fill: #FF0000; stroke: #00ff00 }
+.cls-2 &gt; path { stroke: red }</style></defs><path class="cls-1"/><style><![CDATA[.a{fill:#ff0000}]]></style></svg>"##;
+
+        let (markup, style, report) = extract(input, InlineStyles::Keep);
+
+        assert_eq!(markup, r#"<svg><defs></defs><path class="cls-1"/></svg>"#);
+        assert_eq!(
+            style,
+            ".cls-1 { fill: var(--alarm); stroke: #00ff00 }\n.cls-2 > path { stroke: red }\n\n.a{fill:var(--alarm)}"
+        );
+        assert_eq!(report, "extract-styles: style elements extracted: 2");
+
+        let config = Config::default();
+        let document = Document::parse("<svg><style>\n\n.a { fill: red</style></svg>").unwrap();
+        let mut modifier = Modifier::new(&config, document);
+
+        assert_eq!(
+            ExtractStyles::default()
+                .run(&mut modifier)
+                .err()
+                .unwrap()
+                .to_string(),
+            "Embedded style: Line 3: Unclosed block."
+        );
+    }
+
+    #[test]
+    pub fn test_inline_styles_to_attributes() {
+        let input = r##"<svg><text style="fill: #00ff00; font-size: 12px; stroke: blue !important; cursor: pointer"/><path style="fill:#ff0000"/></svg>"##;
+
+        let (markup, style, report) = extract(input, InlineStyles::Attributes);
+
+        assert_eq!(
+            markup,
+            r#"<svg><text style="stroke: blue !important; cursor: pointer" fill='var(--label)' font-size='12px'/><path fill='var(--alarm)'/></svg>"#
+        );
+        assert!(style.is_empty());
+        assert_eq!(report, "extract-styles: inline styles moved: 2");
+    }
+
+    #[test]
+    pub fn test_inline_styles_to_classes() {
+        let input = r##"<svg><path class="pipe" style="fill: #ff0000"/><path style="fill:#ff0000;"/><text style="fill: #00ff00"/></svg>"##;
+
+        let (markup, style, report) = extract(input, InlineStyles::Classes);
+
+        assert_eq!(
+            markup,
+            r#"<svg><path class="pipe inline-style-1"/><path class='inline-style-1'/><text class='inline-style-2'/></svg>"#
+        );
+        assert_eq!(
+            style,
+            ".inline-style-1 {\n\tfill: var(--alarm);\n}\n\n.inline-style-2 {\n\tfill: var(--label);\n}"
+        );
+        assert_eq!(
+            report,
+            "extract-styles: inline styles moved: 3, classes generated: 2"
+        );
+    }
+}
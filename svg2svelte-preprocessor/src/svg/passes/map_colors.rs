@@ -0,0 +1,166 @@
+use crate::svg::{
+    color_mapping::PAINT_ATTRIBUTES,
+    modifier::{map_declarations, Modifier, Pass, PassReport, DECLARATION_PATTERN},
+};
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/**
+ * Replaces mapped colors in paint attributes and inline `style` declarations
+ * by walking the document, applying the most specific mapping for each
+ * attribute and element first.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct MapColors {}
+
+impl Pass for MapColors {
+    fn name(&self) -> &'static str {
+        "map-colors"
+    }
+
+    fn run(&self, modifier: &mut Modifier) -> Result<PassReport> {
+        let declaration_regex =
+            Regex::new(DECLARATION_PATTERN).expect("Failed to parse declaration pattern.");
+
+        let color_index = modifier.config().color_mappings().index();
+        let mut mapped_values = 0;
+
+        modifier
+            .document_mut()
+            .root_mut()
+            .walk_mut(&mut |element, ancestors| {
+                let map_value = |value: &str, attribute_name: &str| {
+                    color_index.replace(value, |mapping| {
+                        mapping.applies_to(attribute_name, element, ancestors)
+                    })
+                };
+
+                let mapped = element
+                    .attributes()
+                    .iter()
+                    .map(|attribute| match attribute.name() {
+                        "style" => Some(map_declarations(
+                            &declaration_regex,
+                            attribute.value(),
+                            |property, value| {
+                                PAINT_ATTRIBUTES
+                                    .contains(&property)
+                                    .then(|| map_value(value, property))
+                                    .flatten()
+                            },
+                        )),
+                        name if PAINT_ATTRIBUTES.contains(&name) => {
+                            map_value(attribute.value(), name)
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+
+                for (attribute, value) in element.attributes_mut().zip(mapped) {
+                    if let Some(value) = value.filter(|value| value != attribute.value()) {
+                        attribute.set_value(value.as_str());
+                        mapped_values += 1;
+                    }
+                }
+            });
+
+        let mut report = PassReport::new(self.name());
+
+        report.count("values mapped", mapped_values);
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MapColors;
+    use crate::{
+        config::Config,
+        svg::{
+            color_mapping::{ColorMapping, PaintProperty},
+            css_color::CssColor,
+            document::Document,
+            modifier::{Modifier, Pass},
+            selector::Selector,
+            var_color::VarColor,
+        },
+    };
+
+    fn mapped(config: &Config, input: &str) -> String {
+        let mut modifier = Modifier::new(config, Document::parse(input).unwrap());
+
+        MapColors {}.run(&mut modifier).unwrap();
+        modifier.accumulate().to_string()
+    }
+
+    #[test]
+    pub fn test_map_colors() {
+        let input = "<svg fill='#ff00ff' stroke='red' fill='#ff00ff' stroke='blue'></svg>";
+
+        let mut config = Config::default();
+
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("#ff00ff").unwrap(),
+            VarColor::new("--test-color").unwrap(),
+        ));
+
+        config.set_color_mapping(ColorMapping::new(
+            CssColor::new("red").unwrap(),
+            VarColor::new("--test-red").unwrap(),
+        ));
+
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+        let report = MapColors {}.run(&mut modifier).unwrap();
+        let expected = "<svg fill='var(--test-color)' stroke='var(--test-red)' fill='var(--test-color)' stroke='blue'></svg>";
+
+        assert_eq!(modifier.accumulate().to_string(), expected);
+        assert_eq!(report.to_string(), "map-colors: values mapped: 3");
+    }
+
+    #[test]
+    pub fn test_map_colors_by_property_and_element() {
+        let input = r##"<svg><path fill="#000000" stroke="#000000"/><text fill="#000000" style="stroke: #000000">x #000000</text></svg>"##;
+
+        let mut config = Config::default();
+        let black = || CssColor::new("#000000").unwrap();
+
+        config.set_color_mapping(
+            ColorMapping::new(black(), VarColor::new("--pipe").unwrap())
+                .with_property(PaintProperty::Stroke),
+        );
+        config.set_color_mapping(
+            ColorMapping::new(black(), VarColor::new("--label").unwrap())
+                .with_property(PaintProperty::Fill)
+                .with_selector(Selector::new("text").unwrap()),
+        );
+        config.set_color_mapping(ColorMapping::new(black(), VarColor::new("--ink").unwrap()));
+
+        let expected = r##"<svg><path fill="var(--ink)" stroke="var(--pipe)"/><text fill="var(--label)" style="stroke: var(--pipe)">x #000000</text></svg>"##;
+
+        assert_eq!(mapped(&config, input), expected);
+    }
+
+    #[test]
+    pub fn test_map_colors_by_selector() {
+        let input = r##"<svg><g id="oxidizer-tank"><path fill="#FF0000"/><g class="vent"><path fill="#ff0000"/></g></g><path fill="#ff0000"/></svg>"##;
+
+        let mut config = Config::default();
+        let red = || CssColor::new("#ff0000").unwrap();
+
+        config.set_color_mapping(ColorMapping::new(red(), VarColor::new("--alarm").unwrap()));
+        config.set_color_mapping(
+            ColorMapping::new(red(), VarColor::new("--ox-accent").unwrap())
+                .with_selector(Selector::new("#oxidizer-tank *").unwrap()),
+        );
+        config.set_color_mapping(
+            ColorMapping::new(red(), VarColor::new("--vent").unwrap())
+                .with_selector(Selector::new("#oxidizer-* .vent path").unwrap()),
+        );
+
+        let expected = r##"<svg><g id="oxidizer-tank"><path fill="var(--ox-accent)"/><g class="vent"><path fill="var(--vent)"/></g></g><path fill="var(--alarm)"/></svg>"##;
+
+        assert_eq!(mapped(&config, input), expected);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::svg::modifier::{
+    map_declarations, map_token, Modifier, Pass, PassReport, DECLARATION_PATTERN,
+};
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/**
+ * Replaces mapped literal values of non-color properties, both in presentation
+ * attributes and in inline `style` declarations.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct MapTokens {}
+
+impl Pass for MapTokens {
+    fn name(&self) -> &'static str {
+        "map-tokens"
+    }
+
+    fn run(&self, modifier: &mut Modifier) -> Result<PassReport> {
+        let declaration_regex =
+            Regex::new(DECLARATION_PATTERN).expect("Failed to parse declaration pattern.");
+
+        let config = modifier.config();
+        let lookup = |property: &str, value: &str| map_token(config, property, value);
+        let mut mapped_values = 0;
+
+        modifier
+            .document_mut()
+            .root_mut()
+            .walk_mut(&mut |element, _| {
+                for attribute in element.attributes_mut() {
+                    let mapped = match attribute.name() {
+                        "style" => Some(map_declarations(
+                            &declaration_regex,
+                            attribute.value(),
+                            lookup,
+                        )),
+                        name => lookup(name, attribute.value()),
+                    };
+
+                    if let Some(value) = mapped.filter(|value| value != attribute.value()) {
+                        attribute.set_value(value.as_str());
+                        mapped_values += 1;
+                    }
+                }
+            });
+
+        let mut report = PassReport::new(self.name());
+
+        report.count("values mapped", mapped_values);
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MapTokens;
+    use crate::{
+        config::Config,
+        svg::{
+            document::Document,
+            modifier::{Modifier, Pass},
+            token::{TokenMapping, TokenProperty},
+            var_color::VarColor,
+        },
+    };
+
+    #[test]
+    pub fn test_map_tokens() {
+        let input = r#"<svg><path stroke-width="2" stroke-width-x="2"/><text style="font-size:12px; fill: red" font-family="'Roboto Mono', monospace" opacity='0.5'/><path stroke-width="20"/></svg>"#;
+
+        let mut config = Config::default();
+
+        config.set_token_mapping(
+            TokenMapping::new(
+                TokenProperty::StrokeWidth,
+                "2",
+                VarColor::new("--pipe-width").unwrap(),
+            )
+            .unwrap(),
+        );
+        config.set_token_mapping(
+            TokenMapping::new(
+                TokenProperty::FontSize,
+                "12px",
+                VarColor::new("--label-size").unwrap(),
+            )
+            .unwrap(),
+        );
+        config.set_token_mapping(
+            TokenMapping::new(
+                TokenProperty::FontFamily,
+                "'Roboto Mono', monospace",
+                VarColor::new("--mono").unwrap(),
+            )
+            .unwrap(),
+        );
+        config.set_token_mapping(
+            TokenMapping::new(
+                TokenProperty::Opacity,
+                "0.5",
+                VarColor::new("--dim").unwrap(),
+            )
+            .unwrap(),
+        );
+
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+        let report = MapTokens {}.run(&mut modifier).unwrap();
+        let expected = r#"<svg><path stroke-width="var(--pipe-width)" stroke-width-x="2"/><text style="font-size:var(--label-size); fill: red" font-family="var(--mono)" opacity='var(--dim)'/><path stroke-width="20"/></svg>"#;
+
+        assert_eq!(modifier.accumulate().to_string(), expected);
+        assert_eq!(report.to_string(), "map-tokens: values mapped: 4");
+    }
+}
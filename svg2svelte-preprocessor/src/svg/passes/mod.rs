@@ -0,0 +1,8 @@
+pub mod accessibility_metadata;
+pub mod extract_classes;
+pub mod extract_images;
+pub mod extract_styles;
+pub mod map_colors;
+pub mod map_tokens;
+pub mod root_attributes;
+pub mod strip_metadata;
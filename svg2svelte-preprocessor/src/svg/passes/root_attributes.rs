@@ -0,0 +1,136 @@
+use crate::svg::{
+    modifier::{attribute_values, changed_attributes, Modifier, Pass, PassReport},
+    root_rule::RootRule,
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/**
+ * Applies root rules to the attributes of the root `<svg>`, in order.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct RootAttributes {
+    #[serde(default = "RootRule::defaults")]
+    rules: Vec<RootRule>,
+}
+
+impl Default for RootAttributes {
+    fn default() -> Self {
+        Self::new(RootRule::defaults())
+    }
+}
+
+impl RootAttributes {
+    pub fn new(rules: Vec<RootRule>) -> Self {
+        Self { rules }
+    }
+}
+
+impl Pass for RootAttributes {
+    fn name(&self) -> &'static str {
+        "root-attributes"
+    }
+
+    fn options(&self) -> Option<String> {
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| rule.to_string())
+            .collect::<Vec<_>>();
+
+        match rules.is_empty() {
+            true => Some("no rules".to_string()),
+            false => Some(rules.join(", ")),
+        }
+    }
+
+    fn run(&self, modifier: &mut Modifier) -> Result<PassReport> {
+        let root = modifier.document_mut().root_mut();
+        let before = attribute_values(root.attributes());
+
+        for rule in self.rules.iter() {
+            rule.apply(root)?;
+        }
+
+        let mut report = PassReport::new(self.name());
+
+        report.count(
+            "attributes changed",
+            changed_attributes(&before, &attribute_values(root.attributes())),
+        );
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RootAttributes;
+    use crate::{
+        config::Config,
+        svg::{
+            document::Document,
+            modifier::{Modifier, Pass},
+            root_rule::RootRule,
+        },
+    };
+
+    fn fixed(input: &str) -> String {
+        let config = Config::default();
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+
+        RootAttributes::default().run(&mut modifier).unwrap();
+        modifier.accumulate().to_string()
+    }
+
+    #[test]
+    pub fn test_modify_empty_tag() {
+        let result = fixed(r#"<!-- Soar!!! --><svg viewBox="-5 0 10 10"><!-- comment --></svg>"#);
+
+        assert!(result.contains("Soar!!"));
+        assert!(result.contains("comment"));
+        assert!(result.contains("class='diagram'"));
+        assert!(result.contains("fill='none'"));
+        assert!(result.contains("xmlns='http://www.w3.org/2000/svg'"));
+        assert!(result.contains(r#"viewBox="-5 0 10 10""#));
+
+        let config = Config::default();
+        let mut modifier = Modifier::new(&config, Document::parse("<svg/>").unwrap());
+        assert!(RootAttributes::default().run(&mut modifier).is_err());
+    }
+
+    #[test]
+    pub fn test_modify_tag_with_dimensions() {
+        let result = fixed(r#"<!-- whyyy --><svg width="69" height="420"><!-- :( --></svg>"#);
+
+        assert!(result.contains("class='diagram'"));
+        assert!(result.contains("fill='none'"));
+        assert!(result.contains("xmlns='http://www.w3.org/2000/svg'"));
+        assert!(result.contains("viewBox='0 0 69 420'"));
+    }
+
+    #[test]
+    pub fn test_report_changed_attributes() {
+        let config = Config::default();
+        let document = Document::parse(r#"<svg class="diagram" width="10" fill="red"/>"#).unwrap();
+        let mut modifier = Modifier::new(&config, document);
+
+        let report = RootAttributes::new(vec![
+            RootRule::Set {
+                name: "class".to_string(),
+                value: "diagram".to_string(),
+            },
+            RootRule::Set {
+                name: "fill".to_string(),
+                value: "none".to_string(),
+            },
+            RootRule::Remove {
+                name: "width".to_string(),
+            },
+        ])
+        .run(&mut modifier)
+        .unwrap();
+
+        assert_eq!(report.to_string(), "root-attributes: attributes changed: 2");
+    }
+}
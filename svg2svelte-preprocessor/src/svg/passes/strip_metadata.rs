@@ -0,0 +1,144 @@
+use crate::svg::{
+    document::Node,
+    modifier::{Modifier, Pass, PassReport},
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_NAMESPACES: [&str; 2] = ["sodipodi", "inkscape"];
+const DEFAULT_ELEMENTS: [&str; 1] = ["metadata"];
+
+/**
+ * Removes editor metadata: the listed elements, and every element and attribute
+ * in one of the listed namespaces along with the `xmlns:` declaration of the namespace.
+ */
+#[derive(Serialize, Deserialize)]
+pub struct StripMetadata {
+    /**
+     * Namespace prefixes (such as `inkscape`) whose elements and attributes are removed.
+     */
+    #[serde(default = "StripMetadata::default_namespaces")]
+    namespaces: Vec<String>,
+    /**
+     * Element names (such as `metadata`) that are removed along with their content.
+     */
+    #[serde(default = "StripMetadata::default_elements")]
+    elements: Vec<String>,
+}
+
+impl Default for StripMetadata {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl StripMetadata {
+    /**
+     * Lists that are not given keep their defaults.
+     */
+    pub fn new(namespaces: Option<Vec<String>>, elements: Option<Vec<String>>) -> Self {
+        Self {
+            namespaces: namespaces.unwrap_or_else(Self::default_namespaces),
+            elements: elements.unwrap_or_else(Self::default_elements),
+        }
+    }
+
+    fn default_namespaces() -> Vec<String> {
+        DEFAULT_NAMESPACES.map(String::from).to_vec()
+    }
+
+    fn default_elements() -> Vec<String> {
+        DEFAULT_ELEMENTS.map(String::from).to_vec()
+    }
+}
+
+impl Pass for StripMetadata {
+    fn name(&self) -> &'static str {
+        "strip-metadata"
+    }
+
+    fn options(&self) -> Option<String> {
+        let list = |names: &[String]| match names.is_empty() {
+            true => "none".to_string(),
+            false => names.join(", "),
+        };
+
+        Some(format!(
+            "namespaces {}; elements {}",
+            list(&self.namespaces),
+            list(&self.elements)
+        ))
+    }
+
+    fn run(&self, modifier: &mut Modifier) -> Result<PassReport> {
+        let is_stripped_name = |name: &str| {
+            let namespace = name.strip_prefix("xmlns:").unwrap_or(name);
+
+            namespace.split_once(':').map_or(
+                name.starts_with("xmlns:")
+                    && self.namespaces.iter().any(|stripped| stripped == namespace),
+                |(prefix, _)| self.namespaces.iter().any(|stripped| stripped == prefix),
+            )
+        };
+
+        let mut removed_elements = 0;
+        let mut removed_attributes = 0;
+
+        modifier
+            .document_mut()
+            .root_mut()
+            .walk_mut(&mut |element, _| {
+                let (attributes, children) = (element.attributes().len(), element.children().len());
+
+                element.retain_attributes(|attribute| !is_stripped_name(attribute.name()));
+                element.retain_children(|child| match child {
+                    Node::Element(child) => {
+                        !is_stripped_name(child.name())
+                            && !self
+                                .elements
+                                .iter()
+                                .any(|stripped| stripped == child.name())
+                    }
+                    _ => true,
+                });
+
+                removed_attributes += attributes - element.attributes().len();
+                removed_elements += children - element.children().len();
+            });
+
+        let mut report = PassReport::new(self.name());
+
+        report.count("elements removed", removed_elements);
+        report.count("attributes removed", removed_attributes);
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StripMetadata;
+    use crate::{
+        config::Config,
+        svg::{
+            document::Document,
+            modifier::{Modifier, Pass},
+        },
+    };
+
+    #[test]
+    pub fn test_strip_metadata() {
+        let input = r#"<svg xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" xmlns:sodipodi="x" inkscape:version="1.3" width="10"><sodipodi:namedview id="base"/><metadata><rdf:RDF/></metadata><g inkscape:label="Layer 1" id="layer1"><path/></g></svg>"#;
+
+        let config = Config::default();
+        let mut modifier = Modifier::new(&config, Document::parse(input).unwrap());
+        let report = StripMetadata::default().run(&mut modifier).unwrap();
+        let expected = r#"<svg width="10"><g id="layer1"><path/></g></svg>"#;
+
+        assert_eq!(modifier.accumulate().to_string(), expected);
+        assert_eq!(
+            report.to_string(),
+            "strip-metadata: elements removed: 2, attributes removed: 4"
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use super::geometry::{PathScanner, Point};
+use anyhow::{anyhow, Result};
+
+/**
+ * One path command in absolute form. The kind is the uppercase command letter, and arc flags
+ * are stored as `0.0` or `1.0` among the arguments.
+ */
+#[derive(Debug, Clone, PartialEq)]
+struct PathCommand {
+    kind: char,
+    arguments: Vec<f64>,
+}
+
+/**
+ * Path data normalized to absolute commands, so it can be rewritten in its shortest form.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathData {
+    commands: Vec<PathCommand>,
+}
+
+impl PathData {
+    pub fn parse(data: &str) -> Result<Self> {
+        let mut scanner = PathScanner::new(data);
+        let mut commands = Vec::new();
+
+        let mut current: Point = (0.0, 0.0);
+        let mut subpath_start: Point = (0.0, 0.0);
+        let mut previous = None;
+
+        loop {
+            scanner.skip_separators();
+
+            if scanner.peek().is_none() {
+                break;
+            }
+
+            let command = match (scanner.command(), previous) {
+                (Some(command), _) => command,
+                (None, Some('M')) => 'L',
+                (None, Some('m')) => 'l',
+                (None, Some('Z' | 'z') | None) => {
+                    return Err(anyhow!("Path data has coordinates without a command."))
+                }
+                (None, Some(previous)) => previous,
+            };
+
+            previous = Some(command);
+
+            let relative = command.is_ascii_lowercase();
+            let offset = |value: f64, origin: f64| match relative {
+                true => origin + value,
+                false => value,
+            };
+            let point = |scanner: &mut PathScanner| -> Result<Point> {
+                let (x, y) = scanner.point()?;
+                Ok((offset(x, current.0), offset(y, current.1)))
+            };
+
+            let kind = command.to_ascii_uppercase();
+            let (arguments, end) = match kind {
+                'M' | 'L' | 'T' => {
+                    let end = point(&mut scanner)?;
+                    (vec![end.0, end.1], end)
+                }
+                'H' => {
+                    let x = offset(scanner.number()?, current.0);
+                    (vec![x], (x, current.1))
+                }
+                'V' => {
+                    let y = offset(scanner.number()?, current.1);
+                    (vec![y], (current.0, y))
+                }
+                'C' => {
+                    let (first, second, end) = (
+                        point(&mut scanner)?,
+                        point(&mut scanner)?,
+                        point(&mut scanner)?,
+                    );
+                    (
+                        vec![first.0, first.1, second.0, second.1, end.0, end.1],
+                        end,
+                    )
+                }
+                'S' | 'Q' => {
+                    let (control, end) = (point(&mut scanner)?, point(&mut scanner)?);
+                    (vec![control.0, control.1, end.0, end.1], end)
+                }
+                'A' => {
+                    let (rx, ry, rotation) =
+                        (scanner.number()?, scanner.number()?, scanner.number()?);
+                    let (large_arc, sweep) = (scanner.flag()?, scanner.flag()?);
+                    let end = point(&mut scanner)?;
+                    (
+                        vec![
+                            rx,
+                            ry,
+                            rotation,
+                            large_arc as u8 as f64,
+                            sweep as u8 as f64,
+                            end.0,
+                            end.1,
+                        ],
+                        end,
+                    )
+                }
+                'Z' => (Vec::new(), subpath_start),
+                _ => return Err(anyhow!("Unknown path command {}.", command)),
+            };
+
+            if kind == 'M' {
+                subpath_start = end;
+            }
+
+            current = end;
+            commands.push(PathCommand { kind, arguments });
+        }
+
+        Ok(Self { commands })
+    }
+
+    /**
+     * Appends the commands of another path, which starts a new subpath.
+     */
+    pub fn append(&mut self, other: PathData) {
+        self.commands.extend(other.commands);
+    }
+
+    /**
+     * Writes the path in its shortest form: each command is written relative or absolute,
+     * whichever is shorter, numbers are rounded to the precision and written without
+     * unneeded zeros or separators, repeated command letters are left out, and commands
+     * that draw nothing are dropped.
+     */
+    pub fn write(&self, precision: Option<u32>) -> String {
+        let mut data = String::new();
+        let mut previous_letter = None;
+        let mut previous_number: Option<String> = None;
+        let mut current: Point = (0.0, 0.0);
+        let mut subpath_start: Point = (0.0, 0.0);
+
+        for command in self.simplified() {
+            let relative_arguments = command
+                .arguments
+                .iter()
+                .enumerate()
+                .map(|(index, &value)| match (command.kind, index) {
+                    ('H', _) => value - current.0,
+                    ('V', _) => value - current.1,
+                    ('A', 0..=4) => value,
+                    ('A', 5) => value - current.0,
+                    ('A', _) => value - current.1,
+                    (_, index) if index % 2 == 0 => value - current.0,
+                    _ => value - current.1,
+                })
+                .collect::<Vec<_>>();
+
+            let written = [
+                (command.kind.to_ascii_lowercase(), &relative_arguments),
+                (command.kind, &command.arguments),
+            ]
+            .map(|(letter, arguments)| {
+                let numbers = arguments
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &value)| match (command.kind, index) {
+                        ('A', 3 | 4) => format!("{}", value as u8),
+                        _ => format_number(value, precision),
+                    })
+                    .collect::<Vec<_>>();
+
+                let repeats =
+                    previous_letter == Some(letter) && !matches!(letter, 'M' | 'm' | 'Z' | 'z');
+                let mut text = match repeats {
+                    true => String::new(),
+                    false => letter.to_string(),
+                };
+
+                for (index, number) in numbers.iter().enumerate() {
+                    let before = match index {
+                        0 if !repeats => None,
+                        0 => previous_number.as_deref(),
+                        _ => Some(numbers[index - 1].as_str()),
+                    };
+
+                    let needs_separator = before.is_some_and(|before| {
+                        !(number.starts_with('-')
+                            || number.starts_with('.') && before.contains('.'))
+                    });
+
+                    if needs_separator {
+                        text.push(' ');
+                    }
+
+                    text.push_str(number);
+                }
+
+                (letter, text, numbers)
+            });
+
+            let (letter, text, numbers) = match written[0].1.len() <= written[1].1.len() {
+                true => written[0].clone(),
+                false => written[1].clone(),
+            };
+
+            let values = numbers
+                .iter()
+                .map(|number| number.parse::<f64>().unwrap_or_default())
+                .collect::<Vec<_>>();
+            let origin = match letter.is_ascii_lowercase() {
+                true => current,
+                false => (0.0, 0.0),
+            };
+
+            current = match command.kind {
+                'H' => (origin.0 + values[0], current.1),
+                'V' => (current.0, origin.1 + values[0]),
+                'Z' => subpath_start,
+                _ => (
+                    origin.0 + values[values.len() - 2],
+                    origin.1 + values[values.len() - 1],
+                ),
+            };
+
+            if command.kind == 'M' {
+                subpath_start = current;
+            }
+
+            data.push_str(text.as_str());
+            previous_letter = Some(letter);
+            previous_number = numbers.last().cloned();
+        }
+
+        data
+    }
+
+    /**
+     * The commands with moves that are immediately followed by another move, lines that
+     * do not move and lines back to the subpath start before a close path left out.
+     * Horizontal and vertical lines become `H` and `V`.
+     */
+    fn simplified(&self) -> Vec<PathCommand> {
+        let mut commands: Vec<PathCommand> = Vec::new();
+        let mut current: Point = (0.0, 0.0);
+        let mut subpath_start: Point = (0.0, 0.0);
+
+        for (index, command) in self.commands.iter().enumerate() {
+            let next_kind = self.commands.get(index + 1).map(|command| command.kind);
+            let follows_move = commands.last().is_some_and(|last| last.kind == 'M');
+
+            let end = match command.kind {
+                'H' => (command.arguments[0], current.1),
+                'V' => (current.0, command.arguments[0]),
+                'Z' => subpath_start,
+                _ => {
+                    let length = command.arguments.len();
+                    (command.arguments[length - 2], command.arguments[length - 1])
+                }
+            };
+
+            let command = match command.kind {
+                'M' if next_kind == Some('M') => {
+                    current = end;
+                    continue;
+                }
+                'L' | 'H' | 'V' if end == current && !follows_move => continue,
+                'L' | 'H' | 'V' if end == subpath_start && next_kind == Some('Z') => {
+                    current = end;
+                    continue;
+                }
+                'L' if end.1 == current.1 => PathCommand {
+                    kind: 'H',
+                    arguments: vec![end.0],
+                },
+                'L' if end.0 == current.0 => PathCommand {
+                    kind: 'V',
+                    arguments: vec![end.1],
+                },
+                _ => command.clone(),
+            };
+
+            if command.kind == 'M' {
+                subpath_start = end;
+            }
+
+            current = end;
+            commands.push(command);
+        }
+
+        commands
+    }
+}
+
+/**
+ * Writes a number rounded to the precision, without a leading zero or trailing zeros.
+ */
+pub fn format_number(value: f64, precision: Option<u32>) -> String {
+    let value = match precision {
+        Some(precision) => {
+            let scale = 10f64.powi(precision as i32);
+            (value * scale).round() / scale
+        }
+        None => value,
+    };
+
+    let text = format!("{}", value);
+
+    match text.as_str() {
+        "-0" => "0".to_string(),
+        _ if text.starts_with("0.") => text[1..].to_string(),
+        _ if text.starts_with("-0.") => format!("-{}", &text[2..]),
+        _ => text,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_number, PathData};
+
+    fn shortened(data: &str, precision: Option<u32>) -> String {
+        PathData::parse(data).unwrap().write(precision)
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(0.5, None), ".5");
+        assert_eq!(format_number(-0.25, None), "-.25");
+        assert_eq!(format_number(10.0, None), "10");
+        assert_eq!(format_number(1.23456, Some(2)), "1.23");
+        assert_eq!(format_number(-0.001, Some(2)), "0");
+    }
+
+    #[test]
+    fn test_write_shortest_form() {
+        assert_eq!(
+            shortened("M 10 10 L 20 10 L 20 20 L 10 10 Z", None),
+            "m10 10h10v10z"
+        );
+        assert_eq!(
+            shortened("M100 100 L 100.5 100.5 L 101 101", None),
+            "m100 100l.5.5.5.5"
+        );
+        assert_eq!(
+            shortened("M0 0 M 5 5 L 5 5 L 5 5 l 10 -2 C 1 1 2 2 3 3", None),
+            "m5 5h0l10-2C1 1 2 2 3 3"
+        );
+        assert_eq!(
+            shortened("M1.23456 1 a 5 5 0 0 1 10.0001 0", Some(2)),
+            "m1.23 1a5 5 0 0 1 10 0"
+        );
+        assert!(PathData::parse("M 0 0 L").is_err());
+        assert!(PathData::parse("10 10").is_err());
+    }
+
+    #[test]
+    fn test_append_starts_a_new_subpath() {
+        let mut first = PathData::parse("M0 0 h10").unwrap();
+
+        first.append(PathData::parse("m 20 20 l 5 0").unwrap());
+        assert_eq!(first.write(None), "m0 0h10m10 20h5");
+    }
+}
@@ -0,0 +1,336 @@
+use super::css_color::CssColor;
+use anyhow::{anyhow, Result};
+use std::fmt::Display;
+
+/**
+ * The CSS named colors and their hex values, sorted by name.
+ */
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+/**
+ * An opaque sRGB color with components in the range 0 to 1.
+ * Alpha channels of `#rgba` and `#rrggbbaa` colors are ignored.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb {
+    r: f64,
+    g: f64,
+    b: f64,
+}
+
+impl Rgb {
+    pub fn from_css_color(color: &CssColor) -> Result<Self> {
+        let color = color.as_str();
+
+        let hex = match color.strip_prefix('#') {
+            Some(hex_code) => {
+                let digits = hex_code
+                    .chars()
+                    .map(|c| c.to_digit(16).unwrap_or(0))
+                    .collect::<Box<[_]>>();
+
+                match digits.len() {
+                    3 | 4 => digits[..3].iter().fold(0, |acc, d| (acc << 8) | (d * 17)),
+                    6 | 8 => digits[..6].iter().fold(0, |acc, d| (acc << 4) | d),
+                    _ => return Err(anyhow!("Unsupported hex color length in {}.", color)),
+                }
+            }
+            None => NAMED_COLORS
+                .binary_search_by_key(&color, |(name, _)| name)
+                .map(|index| NAMED_COLORS[index].1)
+                .map_err(|_| anyhow!("Unknown CSS color name {}.", color))?,
+        };
+
+        Ok(Self {
+            r: ((hex >> 16) & 0xff) as f64 / 255.0,
+            g: ((hex >> 8) & 0xff) as f64 / 255.0,
+            b: (hex & 0xff) as f64 / 255.0,
+        })
+    }
+
+    /**
+     * The components with the sRGB transfer function removed.
+     */
+    pub fn to_linear(self) -> [f64; 3] {
+        let linearize = |c: f64| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        [linearize(self.r), linearize(self.g), linearize(self.b)]
+    }
+
+    pub fn from_linear([r, g, b]: [f64; 3]) -> Self {
+        let encode = |c: f64| {
+            let c = c.clamp(0.0, 1.0);
+
+            if c <= 0.0031308 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        };
+
+        Self {
+            r: encode(r),
+            g: encode(g),
+            b: encode(b),
+        }
+    }
+
+    /**
+     * Relative luminance as defined by WCAG 2.x.
+     */
+    pub fn relative_luminance(&self) -> f64 {
+        let [r, g, b] = self.to_linear();
+
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /**
+     * CIELAB coordinates under the D65 white point.
+     */
+    pub fn to_lab(self) -> [f64; 3] {
+        let [r, g, b] = self.to_linear();
+
+        let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+        let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+        let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+
+        let f = |t: f64| {
+            if t > 216.0 / 24389.0 {
+                t.cbrt()
+            } else {
+                (24389.0 / 27.0 * t + 16.0) / 116.0
+            }
+        };
+
+        let (fx, fy, fz) = (f(x), f(y), f(z));
+
+        [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    }
+
+    /**
+     * CIE76 color difference (Euclidean distance in CIELAB).
+     */
+    pub fn delta_e(&self, other: &Rgb) -> f64 {
+        let ([l1, a1, b1], [l2, a2, b2]) = (self.to_lab(), other.to_lab());
+
+        ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    /**
+     * WCAG 2.x contrast ratio between two colors, from 1 to 21.
+     */
+    pub fn contrast_ratio(&self, other: &Rgb) -> f64 {
+        let (a, b) = (self.relative_luminance(), other.relative_luminance());
+
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+impl Display for Rgb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let to_byte = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        write!(
+            f,
+            "#{:02x}{:02x}{:02x}",
+            to_byte(self.r),
+            to_byte(self.g),
+            to_byte(self.b)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rgb;
+    use crate::svg::css_color::CssColor;
+
+    fn rgb(color: &str) -> Rgb {
+        Rgb::from_css_color(&CssColor::new(color).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_hex_and_names() {
+        assert_eq!(rgb("#f0a"), rgb("#ff00aa"));
+        assert_eq!(rgb("#ff00aa80"), rgb("#ff00aa"));
+        assert_eq!(rgb("rebeccapurple"), rgb("#663399"));
+        assert_eq!(rgb("#ABCDEF").to_string(), "#abcdef");
+        assert!(Rgb::from_css_color(&CssColor::new("notacolor").unwrap()).is_err());
+        assert!(Rgb::from_css_color(&CssColor::new("#12345").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black_on_white = rgb("black").contrast_ratio(&rgb("white"));
+        let same = rgb("#777777").contrast_ratio(&rgb("#777777"));
+
+        assert!((black_on_white - 21.0).abs() < 1e-9);
+        assert!((same - 1.0).abs() < 1e-9);
+        assert!((rgb("#767676").contrast_ratio(&rgb("white")) - 4.54).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_linear_round_trip_and_delta_e() {
+        let color = rgb("#3a7bd5");
+
+        assert_eq!(Rgb::from_linear(color.to_linear()).to_string(), "#3a7bd5");
+        assert!((rgb("white").to_lab()[0] - 100.0).abs() < 0.01);
+        assert!(rgb("#808080").delta_e(&rgb("#808080")) < 1e-9);
+        assert!((rgb("black").delta_e(&rgb("white")) - 100.0).abs() < 0.01);
+    }
+}
@@ -0,0 +1,285 @@
+use super::{document::Element, geometry::absolute_length};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+const ALIGNMENTS: [&str; 9] = [
+    "xMinYMin", "xMidYMin", "xMaxYMin", "xMinYMid", "xMidYMid", "xMaxYMid", "xMinYMax", "xMidYMax",
+    "xMaxYMax",
+];
+
+/**
+ * A rule applied to the attributes of the root `<svg>` element.
+ * Rules run in the order they are listed in the config, so `remove` for `width`
+ * and `height` should come after `derive-view-box`.
+ */
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(
+    tag = "rule",
+    rename_all = "kebab-case",
+    try_from = "UncheckedRootRule"
+)]
+pub enum RootRule {
+    Set { name: String, value: String },
+    SetIfMissing { name: String, value: String },
+    Remove { name: String },
+    DeriveViewBox,
+    PreserveAspectRatio { value: String },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "rule", rename_all = "kebab-case")]
+enum UncheckedRootRule {
+    Set { name: String, value: String },
+    SetIfMissing { name: String, value: String },
+    Remove { name: String },
+    DeriveViewBox,
+    PreserveAspectRatio { value: String },
+}
+
+impl RootRule {
+    /**
+     * The rules the preprocessor has always applied: the `diagram` class, no default fill,
+     * the SVG namespace and a viewBox derived from the dimensions.
+     */
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            RootRule::Set {
+                name: "class".to_string(),
+                value: "diagram".to_string(),
+            },
+            RootRule::Set {
+                name: "fill".to_string(),
+                value: "none".to_string(),
+            },
+            RootRule::Set {
+                name: "xmlns".to_string(),
+                value: SVG_NAMESPACE.to_string(),
+            },
+            RootRule::DeriveViewBox,
+        ]
+    }
+
+    /**
+     * Applies the rule to the root element. Deriving a viewBox fails when the root has no
+     * valid viewBox and its width and height are missing or not in absolute units.
+     */
+    pub fn apply(&self, root: &mut Element) -> Result<()> {
+        match self {
+            RootRule::Set { name, value } => root.set_attribute(name, value),
+            RootRule::SetIfMissing { name, value } => {
+                if root.attribute(name).is_none() {
+                    root.set_attribute(name, value);
+                }
+            }
+            RootRule::Remove { name } => {
+                root.retain_attributes(|attribute| attribute.name() != name)
+            }
+            RootRule::DeriveViewBox => {
+                let view_box = derive_view_box(root)?;
+                root.set_attribute("viewBox", view_box.as_str());
+            }
+            RootRule::PreserveAspectRatio { value } => {
+                root.set_attribute("preserveAspectRatio", value)
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * An existing viewBox is kept as written, including its origin. Otherwise it spans
+ * the width and height of the root converted to user units.
+ */
+fn derive_view_box(root: &Element) -> Result<String> {
+    if let Some(view_box) = root.attribute("viewBox") {
+        let numbers = view_box
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|number| !number.is_empty())
+            .map(|number| {
+                number
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|number| number.is_finite())
+            })
+            .collect::<Option<Vec<_>>>();
+
+        return match numbers.as_deref() {
+            Some(&[_, _, width, height]) if width > 0.0 && height > 0.0 => Ok(view_box.to_string()),
+            _ => Err(anyhow!("Invalid viewBox {}.", view_box)),
+        };
+    }
+
+    let (Some(width), Some(height)) = (root.attribute("width"), root.attribute("height")) else {
+        return Err(anyhow!(
+            "Cannot derive a viewBox without a width and height."
+        ));
+    };
+
+    let (width, height) = (absolute_length(width)?, absolute_length(height)?);
+
+    if width <= 0.0 || height <= 0.0 {
+        return Err(anyhow!("Cannot derive a viewBox from an empty size."));
+    }
+
+    let rounded = |number: f64| (number * 1e4).round() / 1e4;
+
+    Ok(format!("0 0 {} {}", rounded(width), rounded(height)))
+}
+
+impl TryFrom<UncheckedRootRule> for RootRule {
+    type Error = anyhow::Error;
+
+    fn try_from(unchecked: UncheckedRootRule) -> Result<Self> {
+        let is_attribute_name = |name: &str| {
+            name.starts_with(|c: char| c.is_ascii_alphabetic())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+        };
+
+        let rule = match unchecked {
+            UncheckedRootRule::Set { name, value } => RootRule::Set { name, value },
+            UncheckedRootRule::SetIfMissing { name, value } => {
+                RootRule::SetIfMissing { name, value }
+            }
+            UncheckedRootRule::Remove { name } => RootRule::Remove { name },
+            UncheckedRootRule::DeriveViewBox => RootRule::DeriveViewBox,
+            UncheckedRootRule::PreserveAspectRatio { value } => {
+                let mut parts = value.split_whitespace();
+                let is_valid = match (parts.next(), parts.next(), parts.next()) {
+                    (Some(align), meet_or_slice, None) => {
+                        (align == "none" || ALIGNMENTS.contains(&align))
+                            && meet_or_slice.is_none_or(|part| part == "meet" || part == "slice")
+                    }
+                    _ => false,
+                };
+
+                if !is_valid {
+                    return Err(anyhow!("Invalid preserveAspectRatio {}.", value));
+                }
+
+                RootRule::PreserveAspectRatio { value }
+            }
+        };
+
+        match &rule {
+            RootRule::Set { name, .. }
+            | RootRule::SetIfMissing { name, .. }
+            | RootRule::Remove { name }
+                if !is_attribute_name(name) =>
+            {
+                Err(anyhow!("Invalid attribute name {}.", name))
+            }
+            _ => Ok(rule),
+        }
+    }
+}
+
+impl Display for RootRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RootRule::Set { name, value } => write!(f, "set {}='{}'", name, value),
+            RootRule::SetIfMissing { name, value } => {
+                write!(f, "set if missing {}='{}'", name, value)
+            }
+            RootRule::Remove { name } => write!(f, "remove {}", name),
+            RootRule::DeriveViewBox => write!(f, "derive viewBox"),
+            RootRule::PreserveAspectRatio { value } => {
+                write!(f, "preserveAspectRatio='{}'", value)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RootRule;
+    use crate::svg::document::Document;
+
+    fn derived_view_box(input: &str) -> Result<String, String> {
+        let mut document = Document::parse(input).unwrap();
+
+        RootRule::DeriveViewBox
+            .apply(document.root_mut())
+            .map(|_| document.root().attribute("viewBox").unwrap().to_string())
+            .map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn test_root_rules_apply_in_order() {
+        let rules: Vec<RootRule> = serde_json::from_str(
+            r#"[
+                {"rule": "set", "name": "class", "value": "gauge"},
+                {"rule": "set-if-missing", "name": "fill", "value": "none"},
+                {"rule": "set-if-missing", "name": "class", "value": "diagram"},
+                {"rule": "derive-view-box"},
+                {"rule": "remove", "name": "width"},
+                {"rule": "remove", "name": "height"},
+                {"rule": "preserve-aspect-ratio", "value": "xMidYMid slice"}
+            ]"#,
+        )
+        .unwrap();
+
+        let mut document = Document::parse(r#"<svg width="20" height="10"/>"#).unwrap();
+
+        for rule in rules.iter() {
+            rule.apply(document.root_mut()).unwrap();
+        }
+
+        assert_eq!(
+            document.to_string(),
+            "<svg class='gauge' fill='none' viewBox='0 0 20 10' preserveAspectRatio='xMidYMid slice'/>"
+        );
+        assert_eq!(rules[3].to_string(), "derive viewBox");
+    }
+
+    #[test]
+    fn test_root_rules_are_validated() {
+        let invalid = [
+            r#"{"rule": "set", "name": "a b", "value": "x"}"#,
+            r#"{"rule": "remove", "name": ""}"#,
+            r#"{"rule": "preserve-aspect-ratio", "value": "xMidYMid stretch"}"#,
+            r#"{"rule": "rename", "name": "class"}"#,
+        ];
+
+        for rule in invalid {
+            assert!(serde_json::from_str::<RootRule>(rule).is_err(), "{}", rule);
+        }
+
+        assert!(serde_json::from_str::<RootRule>(
+            r#"{"rule": "preserve-aspect-ratio", "value": "none"}"#
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_derive_view_box() {
+        assert_eq!(
+            derived_view_box(r#"<svg width="69px" height="420"/>"#),
+            Ok("0 0 69 420".to_string())
+        );
+        assert_eq!(
+            derived_view_box(r#"<svg width="120mm" height="1in"/>"#),
+            Ok("0 0 453.5433 96".to_string())
+        );
+        assert_eq!(
+            derived_view_box(r#"<svg width="100%" height="50" viewBox="-10 5 200,100"/>"#),
+            Ok("-10 5 200,100".to_string())
+        );
+        assert_eq!(
+            derived_view_box(r#"<svg width="100%" height="50"/>"#),
+            Err("Length 100% is not in an absolute unit.".to_string())
+        );
+        assert_eq!(
+            derived_view_box(r#"<svg width="10em" height="50"/>"#),
+            Err("Length 10em is not in an absolute unit.".to_string())
+        );
+        assert!(derived_view_box(r#"<svg viewBox="0 0 -1 5"/>"#).is_err());
+        assert!(derived_view_box(r#"<svg width="0" height="5"/>"#).is_err());
+        assert!(derived_view_box("<svg/>").is_err());
+    }
+}
@@ -0,0 +1,279 @@
+use super::document::Element;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/**
+ * Limits a mapping to matching elements.
+ *
+ * A selector is a whitespace separated list of compounds, where each compound
+ * must match an ancestor of the element matched by the next one (the CSS descendant combinator).
+ * A compound is `*` or an optional element name followed by an optional `#id` and any `.class`.
+ * Ids are Figma layer names and may use the globs `*` and `?`, for example `#valve-*`.
+ *
+ * Examples: `text`, `#oxidizer-tank *`, `g#valve-* path.outline`.
+ */
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+#[serde(try_from = "String", into = "String")]
+pub struct Selector {
+    text: String,
+    compounds: Vec<Compound>,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+struct Compound {
+    element_name: Option<String>,
+    id_pattern: Option<String>,
+    classes: Vec<String>,
+}
+
+impl Compound {
+    fn parse(text: &str) -> Result<Self> {
+        let mut compound = Compound {
+            element_name: None,
+            id_pattern: None,
+            classes: Vec::new(),
+        };
+
+        if text == "*" {
+            return Ok(compound);
+        }
+
+        let name_end = text.find(['#', '.']).unwrap_or(text.len());
+        let (element_name, mut rest) = text.split_at(name_end);
+
+        if !element_name.is_empty() {
+            if !is_name(element_name, false) {
+                return Err(anyhow!(
+                    "Invalid element name {} in selector.",
+                    element_name
+                ));
+            }
+
+            compound.element_name = Some(element_name.to_string());
+        }
+
+        while let Some(prefix) = rest.chars().next() {
+            let part_end = rest[1..].find(['#', '.']).map_or(rest.len(), |end| end + 1);
+            let part = &rest[1..part_end];
+
+            match prefix {
+                '#' if compound.id_pattern.is_none() && is_name(part, true) => {
+                    compound.id_pattern = Some(part.to_string())
+                }
+                '.' if is_name(part, false) => compound.classes.push(part.to_string()),
+                _ => return Err(anyhow!("Invalid selector part {}.", &rest[..part_end])),
+            }
+
+            rest = &rest[part_end..];
+        }
+
+        Ok(compound)
+    }
+
+    fn matches(&self, element: &Element) -> bool {
+        self.element_name
+            .as_ref()
+            .is_none_or(|name| name == element.name())
+            && self.id_pattern.as_ref().is_none_or(|pattern| {
+                element
+                    .attribute("id")
+                    .is_some_and(|id| glob_matches(pattern, id))
+            })
+            && self
+                .classes
+                .iter()
+                .all(|class| element.classes().any(|c| c == class))
+    }
+}
+
+impl Selector {
+    pub fn new(selector: &str) -> Result<Self> {
+        let compounds = selector
+            .split_whitespace()
+            .map(Compound::parse)
+            .collect::<Result<Vec<_>>>()?;
+
+        if compounds.is_empty() {
+            return Err(anyhow!("Empty selector."));
+        }
+
+        Ok(Self {
+            text: compounds_text(selector),
+            compounds,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.text.as_str()
+    }
+
+    /**
+     * Whether an element, given its ancestors from outermost to innermost, matches.
+     */
+    pub fn matches(&self, element: &Element, ancestors: &[&Element]) -> bool {
+        let (target, required_ancestors) = self
+            .compounds
+            .split_last()
+            .expect("Selectors have at least one compound.");
+
+        if !target.matches(element) {
+            return false;
+        }
+
+        let mut remaining = required_ancestors.iter().rev().peekable();
+
+        for ancestor in ancestors.iter().rev() {
+            if remaining
+                .peek()
+                .is_some_and(|compound| compound.matches(ancestor))
+            {
+                remaining.next();
+            }
+        }
+
+        remaining.peek().is_none()
+    }
+
+    /**
+     * CSS-style specificity as (ids, classes, element names).
+     */
+    pub fn specificity(&self) -> (usize, usize, usize) {
+        self.compounds
+            .iter()
+            .fold((0, 0, 0), |(ids, classes, names), compound| {
+                (
+                    ids + compound.id_pattern.is_some() as usize,
+                    classes + compound.classes.len(),
+                    names + compound.element_name.is_some() as usize,
+                )
+            })
+    }
+}
+
+impl TryFrom<String> for Selector {
+    type Error = anyhow::Error;
+
+    fn try_from(selector: String) -> Result<Self> {
+        Self::new(selector.as_str())
+    }
+}
+
+impl From<Selector> for String {
+    fn from(selector: Selector) -> Self {
+        selector.text
+    }
+}
+
+fn compounds_text(selector: &str) -> String {
+    selector.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_name(name: &str, allow_globs: bool) -> bool {
+    !name.is_empty()
+        && name.chars().all(|c| {
+            c.is_alphanumeric()
+                || matches!(c, '-' | '_' | ':')
+                || (allow_globs && matches!(c, '*' | '?'))
+        })
+}
+
+/**
+ * Matches text against a pattern where `*` is any run of characters and `?` is any one character.
+ */
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let (pattern, text) = (pattern.as_bytes(), text.as_bytes());
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::{glob_matches, Selector};
+    use crate::svg::document::{Document, Element};
+
+    const INPUT: &str = r#"<svg>
+        <g id="oxidizer-tank" class="tank">
+            <g id="valve-12"><path id="outline" class="thick line"/></g>
+        </g>
+        <text id="label"/>
+    </svg>"#;
+
+    fn matches(selector: &str, id: &str) -> bool {
+        let document = Document::parse(INPUT).unwrap();
+        let selector = Selector::new(selector).unwrap();
+        let mut found = None;
+
+        document.root().walk(&mut |element: &Element, ancestors| {
+            if element.attribute("id") == Some(id) {
+                found = Some(selector.matches(element, ancestors));
+            }
+        });
+
+        found.unwrap()
+    }
+
+    #[test]
+    fn test_selector_new() {
+        assert!(Selector::new("text").is_ok());
+        assert!(Selector::new("linearGradient").is_ok());
+        assert!(Selector::new("#oxidizer-tank  *").unwrap().as_str() == "#oxidizer-tank *");
+        assert!(Selector::new("g#valve-*.open.thick").is_ok());
+        assert!(Selector::new("").is_err());
+        assert!(Selector::new("g > path").is_err());
+        assert!(Selector::new("#a#b").is_err());
+        assert!(Selector::new("path.").is_err());
+    }
+
+    #[test]
+    fn test_selector_matches() {
+        assert!(matches("text", "label"));
+        assert!(!matches("text", "outline"));
+        assert!(matches("#oxidizer-tank *", "outline"));
+        assert!(matches("#oxidizer-tank *", "valve-12"));
+        assert!(!matches("#oxidizer-tank *", "oxidizer-tank"));
+        assert!(!matches("#oxidizer-tank *", "label"));
+        assert!(matches("#valve-* path", "outline"));
+        assert!(matches(".tank g#valve-?? .thick.line", "outline"));
+        assert!(!matches("path.dashed", "outline"));
+    }
+
+    #[test]
+    fn test_selector_specificity() {
+        let selector = Selector::new("#oxidizer-tank g.open path").unwrap();
+
+        assert_eq!(selector.specificity(), (1, 1, 2));
+        assert_eq!(Selector::new("*").unwrap().specificity(), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("valve-*", "valve-12"));
+        assert!(glob_matches("*-12", "valve-12"));
+        assert!(glob_matches("v?lve*2", "valve-12"));
+        assert!(!glob_matches("pump-*", "valve-12"));
+        assert!(!glob_matches("valve-?", "valve-12"));
+    }
+}
@@ -0,0 +1,254 @@
+use super::var_color::VarColor;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/**
+ * Units accepted for lengths in token values.
+ */
+const LENGTH_UNITS: &[&str] = &[
+    "px", "em", "rem", "ex", "ch", "vw", "vh", "vmin", "vmax", "cm", "mm", "q", "in", "pt", "pc",
+    "%",
+];
+
+const FONT_SIZE_KEYWORDS: &[&str] = &[
+    "xx-small",
+    "x-small",
+    "small",
+    "medium",
+    "large",
+    "x-large",
+    "xx-large",
+    "xxx-large",
+    "smaller",
+    "larger",
+];
+
+/**
+ * A non-color presentation property whose values can be mapped to CSS variables.
+ */
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum TokenProperty {
+    StrokeWidth,
+    Opacity,
+    FontSize,
+    FontFamily,
+    StrokeDasharray,
+}
+
+/**
+ * Maps one literal value of a property to a CSS variable.
+ * The value is validated against the grammar of its property.
+ */
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[serde(try_from = "UncheckedTokenMapping")]
+pub struct TokenMapping {
+    property: TokenProperty,
+    value: String,
+    var: VarColor,
+}
+
+#[derive(Deserialize)]
+struct UncheckedTokenMapping {
+    property: TokenProperty,
+    value: String,
+    var: VarColor,
+}
+
+impl TokenProperty {
+    pub const ALL: [TokenProperty; 5] = [
+        TokenProperty::StrokeWidth,
+        TokenProperty::Opacity,
+        TokenProperty::FontSize,
+        TokenProperty::FontFamily,
+        TokenProperty::StrokeDasharray,
+    ];
+
+    pub fn new(name: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|property| property.as_str() == name)
+            .ok_or(anyhow!("Unsupported token property."))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenProperty::StrokeWidth => "stroke-width",
+            TokenProperty::Opacity => "opacity",
+            TokenProperty::FontSize => "font-size",
+            TokenProperty::FontFamily => "font-family",
+            TokenProperty::StrokeDasharray => "stroke-dasharray",
+        }
+    }
+
+    pub fn validate(&self, value: &str) -> Result<()> {
+        let is_valid = match self {
+            TokenProperty::StrokeWidth => is_length(value),
+            TokenProperty::Opacity => match value.strip_suffix('%') {
+                Some(percentage) => is_number_in(percentage, 0.0, 100.0),
+                None => is_number_in(value, 0.0, 1.0),
+            },
+            TokenProperty::FontSize => FONT_SIZE_KEYWORDS.contains(&value) || is_length(value),
+            TokenProperty::FontFamily => value.split(',').all(is_font_family),
+            TokenProperty::StrokeDasharray => {
+                value == "none"
+                    || value
+                        .split(|c: char| c.is_whitespace() || c == ',')
+                        .filter(|part| !part.is_empty())
+                        .all(is_length)
+            }
+        };
+
+        if is_valid {
+            Ok(())
+        } else {
+            Err(anyhow!("Invalid value for {}.", self.as_str()))
+        }
+    }
+}
+
+impl Display for TokenProperty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl TokenMapping {
+    pub fn new(property: TokenProperty, value: &str, var: VarColor) -> Result<Self> {
+        let value = value.trim();
+        property.validate(value)?;
+
+        Ok(Self {
+            property,
+            value: value.to_string(),
+            var,
+        })
+    }
+
+    pub fn property(&self) -> TokenProperty {
+        self.property
+    }
+
+    pub fn value(&self) -> &str {
+        self.value.as_str()
+    }
+
+    pub fn var(&self) -> &VarColor {
+        &self.var
+    }
+}
+
+impl TryFrom<UncheckedTokenMapping> for TokenMapping {
+    type Error = anyhow::Error;
+
+    fn try_from(unchecked: UncheckedTokenMapping) -> Result<Self> {
+        Self::new(unchecked.property, unchecked.value.as_str(), unchecked.var)
+    }
+}
+
+fn is_number_in(value: &str, min: f64, max: f64) -> bool {
+    value
+        .parse::<f64>()
+        .is_ok_and(|number| number.is_finite() && (min..=max).contains(&number))
+}
+
+/**
+ * A non-negative number, optionally followed by a length unit or percent sign.
+ */
+fn is_length(value: &str) -> bool {
+    let unit_start = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '+'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(unit_start);
+
+    is_number_in(number, 0.0, f64::MAX)
+        && (unit.is_empty() || LENGTH_UNITS.contains(&unit.to_lowercase().as_str()))
+}
+
+/**
+ * A quoted family name or a sequence of identifiers such as `Inter` or `Open Sans`.
+ */
+fn is_font_family(family: &str) -> bool {
+    let family = family.trim();
+
+    let is_quoted = |quote: char| {
+        family.len() >= 2
+            && family.starts_with(quote)
+            && family.ends_with(quote)
+            && !family[1..family.len() - 1].contains(quote)
+    };
+
+    if is_quoted('"') || is_quoted('\'') {
+        return true;
+    }
+
+    !family.is_empty()
+        && family.split_whitespace().all(|identifier| {
+            !identifier.starts_with(|c: char| c.is_ascii_digit())
+                && identifier
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TokenMapping, TokenProperty};
+    use crate::svg::var_color::VarColor;
+
+    #[test]
+    fn test_validate_property_grammars() {
+        let valid = [
+            (TokenProperty::StrokeWidth, "2"),
+            (TokenProperty::StrokeWidth, "1.5px"),
+            (TokenProperty::Opacity, "0.4"),
+            (TokenProperty::Opacity, "40%"),
+            (TokenProperty::FontSize, "12pt"),
+            (TokenProperty::FontSize, "large"),
+            (TokenProperty::FontFamily, "'Roboto Mono', monospace"),
+            (TokenProperty::FontFamily, "Open Sans"),
+            (TokenProperty::StrokeDasharray, "4 2, 1"),
+            (TokenProperty::StrokeDasharray, "none"),
+        ];
+
+        let invalid = [
+            (TokenProperty::StrokeWidth, "-2"),
+            (TokenProperty::StrokeWidth, "2furlongs"),
+            (TokenProperty::Opacity, "1.5"),
+            (TokenProperty::FontSize, "big"),
+            (TokenProperty::FontFamily, "'Roboto"),
+            (TokenProperty::FontFamily, "3D Font"),
+            (TokenProperty::StrokeDasharray, "4 -2"),
+        ];
+
+        for (property, value) in valid {
+            assert!(property.validate(value).is_ok(), "{} {}", property, value);
+        }
+
+        for (property, value) in invalid {
+            assert!(property.validate(value).is_err(), "{} {}", property, value);
+        }
+    }
+
+    #[test]
+    fn test_token_mapping_deserialize_is_validated() {
+        let valid = r#"{"property":"stroke-width","value":"2","var":"--pipe-width"}"#;
+        let invalid = r#"{"property":"opacity","value":"2","var":"--dim"}"#;
+
+        let expected = TokenMapping::new(
+            TokenProperty::StrokeWidth,
+            "2",
+            VarColor::new("--pipe-width").unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<TokenMapping>(valid).unwrap(),
+            expected
+        );
+        assert!(serde_json::from_str::<TokenMapping>(invalid).is_err());
+        assert!(TokenProperty::new("stroke-dasharray").is_ok());
+        assert!(TokenProperty::new("fill").is_err());
+    }
+}
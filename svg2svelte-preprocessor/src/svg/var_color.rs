@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, PartialEq)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
 pub struct VarColor(String);
 
 impl VarColor {
@@ -20,6 +20,7 @@ impl VarColor {
     }
 }
 
+#[cfg(test)]
 mod test {
     use super::VarColor;
 
@@ -0,0 +1,37 @@
+use crate::svg::{css_color::CssColor, var_color::VarColor};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/**
+ * Concrete values for the mapped CSS variables under one GUI theme.
+ * The background is the canvas the diagram is drawn on, which is white when unset.
+ */
+#[derive(Serialize, Deserialize, Default)]
+pub struct Theme {
+    #[serde(default)]
+    background: Option<CssColor>,
+    #[serde(default)]
+    colors: HashMap<VarColor, CssColor>,
+}
+
+impl Theme {
+    #[inline(always)]
+    pub fn background(&self) -> Option<&CssColor> {
+        self.background.as_ref()
+    }
+
+    #[inline(always)]
+    pub fn color(&self, var_color: &VarColor) -> Option<&CssColor> {
+        self.colors.get(var_color)
+    }
+
+    #[inline(always)]
+    pub fn colors(&self) -> &HashMap<VarColor, CssColor> {
+        &self.colors
+    }
+
+    #[inline(always)]
+    pub fn set_color(&mut self, var_color: VarColor, css_color: CssColor) {
+        self.colors.insert(var_color, css_color);
+    }
+}